@@ -0,0 +1,42 @@
+//! Compare serial and parallel parsing of a large generated RIS file.
+//!
+//! Run with `cargo run --release --features std-threads --example parallel_benchmark [entries]`.
+use std::time::Instant;
+
+use ris::RIS;
+
+fn main() {
+    let entries = std::env::args()
+        .nth(1)
+        .map_or(100_000, |n| n.parse().expect("invalid number of entries"));
+
+    let s: String = (0..entries)
+        .map(|i| {
+            format!(
+                "TY  - JOUR\nID  - entry{}\nT1  - A Mathematical Theory of Communication\n\
+                 AU  - Shannon, Claude E.\nAU  - Weaver, Warren\nPY  - 1948/07//\n\
+                 JO  - Bell System Technical Journal\nVL  - 27\nSP  - 379\nEP  - 423\n\
+                 KW  - information theory\nER  - \n",
+                i
+            )
+        })
+        .collect();
+
+    let start = Instant::now();
+    let serial: RIS = s.parse().unwrap();
+    let serial_time = start.elapsed();
+
+    let start = Instant::now();
+    let parallel = RIS::from_str_parallel(&s).unwrap();
+    let parallel_time = start.elapsed();
+
+    assert_eq!(serial, parallel);
+    println!(
+        "{} entries ({} MB): serial {:?}, parallel {:?} on {} threads",
+        entries,
+        s.len() / 1_000_000,
+        serial_time,
+        parallel_time,
+        std::thread::available_parallelism().map_or(1, |n| n.get())
+    );
+}
@@ -1,8 +1,15 @@
 //! A simple [RIS bibliography file](https://en.wikipedia.org/wiki/RIS_%28file_format%29) (de)serializer for Rust.
 use std::{
+    borrow::Cow,
+    cmp::Ordering,
+    collections::{BTreeMap, HashMap, HashSet},
     convert::Infallible,
     fmt::{self, Display, Formatter},
+    io::{self, BufRead},
+    iter::FromIterator,
+    marker::PhantomData,
     str::FromStr,
+    time::{SystemTime, UNIX_EPOCH},
 };
 
 use lazy_static::lazy_static;
@@ -15,1120 +22,7790 @@ use regex::Regex;
 ///
 /// This type implements [Display](std::fmt::Display) and [FromStr](std::str::FromStr) to (de)serialize to/from strings.
 ///
+/// Form feeds (`\x0C`), which some systems use to separate concatenated RIS files, are ignored at the start
+/// and end of lines, and lines made only of form feeds are ignored between entries.
+/// A UTF-8 byte order mark at the start of the file is also ignored, as are blank lines between entries.
+/// Lines can end with either `\n` or `\r\n`.
+///
 /// See [Entry](crate::Entry) for more information.
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct RIS(pub Vec<Entry>);
 
-impl FromStr for RIS {
-    type Err = ParseError;
+impl RIS {
+    /// Populate the `id` of every entry which doesn't have one, using a caller-provided generator.
+    ///
+    /// The generator receives the entry and its index in the list. Entries which already have an `id` are left untouched.
+    pub fn assign_ids(&mut self, mut f: impl FnMut(&Entry, usize) -> String) {
+        for (i, entry) in self.0.iter_mut().enumerate() {
+            if entry.id.is_none() {
+                entry.id = Some(f(entry, i));
+            }
+        }
+    }
 
-    /// Parse a RIS file from a string.
+    /// Drop the month, day and other info from the `primary_date` and `secondary_date` of every entry, leaving only the year.
+    pub fn truncate_dates_to_year(&mut self) {
+        for entry in self.0.iter_mut() {
+            for date in entry
+                .primary_date
+                .iter_mut()
+                .chain(entry.secondary_date.iter_mut())
+            {
+                *date = PublicationDate::new(date.year, None, None, None);
+            }
+        }
+    }
+
+    /// Parse a RIS file from a string, with the given [ParseOptions](crate::ParseOptions).
     /// See [Entry](crate::Entry) for more information on how keys are mapped to fields.
-    fn from_str(s: &str) -> Result<RIS, Self::Err> {
-        use ParseErrorKind::*;
+    pub fn from_str_with_options(s: &str, options: &ParseOptions) -> Result<RIS, ParseError> {
+        EntryIter::from_str(s, options.clone())
+            .collect::<Result<_, _>>()
+            .map(RIS)
+    }
 
-        let mut entries = Vec::new();
-        let mut line_no = 0;
-        let mut current_entry: PartialEntry = PartialEntry::new();
+    /// Parse only the first `n` entries of a RIS file from a string, ignoring the rest of it.
+    pub fn from_str_limited(s: &str, n: usize) -> Result<RIS, ParseError> {
+        EntryIter::from_str(s, ParseOptions::default())
+            .take(n)
+            .collect::<Result<_, _>>()
+            .map(RIS)
+    }
 
-        for line in s.lines() {
-            line_no += 1;
+    /// Parse a RIS file from a string, splitting it into chunks of whole entries which are parsed on separate threads.
+    ///
+    /// This is faster than [from_str](std::str::FromStr::from_str) on large files with many entries, and gives the same
+    /// result, with the entries in the same order.
+    /// If several chunks fail to parse, the error in the earliest one is returned, with its line number in the whole file.
+    ///
+    /// Requires the `std-threads` feature. The chunks are parsed on scoped standard threads,
+    /// one per available core.
+    #[cfg(feature = "std-threads")]
+    pub fn from_str_parallel(s: &str) -> Result<RIS, ParseError> {
+        let threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+        RIS::from_str_in_chunks(s, threads)
+    }
 
-            if current_entry.parse_line(line, line_no)? == ParseState::End {
-                entries.push(current_entry.entry.unwrap());
-                current_entry = PartialEntry::new();
-            }
+    /// Parse a RIS file from a string in about `n` chunks on separate threads.
+    #[cfg(feature = "std-threads")]
+    fn from_str_in_chunks(s: &str, n: usize) -> Result<RIS, ParseError> {
+        if n < 2 {
+            return s.parse();
         }
 
-        if current_entry.state == ParseState::InProgress {
-            Err(ParseError::new(line_no, UnterminatedEntry))
-        } else {
-            Ok(RIS(entries))
+        let chunks = split_entry_chunks(s, n);
+
+        let results: Vec<_> = std::thread::scope(|scope| {
+            let handles: Vec<_> = chunks
+                .iter()
+                .map(|&(_, chunk)| scope.spawn(move || RIS::from_str(chunk)))
+                .collect();
+
+            handles
+                .into_iter()
+                .map(|handle| handle.join().unwrap())
+                .collect()
+        });
+
+        let mut entries = Vec::new();
+
+        for (result, &(first_line, _)) in results.into_iter().zip(&chunks) {
+            match result {
+                Ok(mut ris) => entries.append(&mut ris.0),
+                Err(e) => return Err(ParseError::new(first_line + e.line_no, e.kind)),
+            }
         }
+
+        Ok(RIS(entries))
     }
-}
 
-struct PartialEntry {
-    entry: Option<Entry>,
-    state: ParseState,
-}
+    /// Parse a RIS file from a reader.
+    /// See [EntryReader](crate::EntryReader) to read the entries one at a time instead.
+    pub fn from_reader<R: BufRead>(reader: R) -> Result<RIS, ReadError> {
+        EntryReader::new(reader).collect::<Result<_, _>>().map(RIS)
+    }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum ParseState {
-    /// Before TY
-    Start,
-    /// After TY and before ER
-    InProgress,
-    /// After ER
-    End,
-}
+    /// Parse a RIS file embedded in other text, such as an email with headers and MIME boundaries.
+    ///
+    /// This uses a simple heuristic: everything before the first line starting with `TY  - `
+    /// and after the last line starting with `ER  -` is ignored, and the rest is parsed as usual.
+    /// Line numbers in errors refer to the whole input.
+    pub fn from_str_extract(s: &str) -> Result<RIS, ParseError> {
+        let lines: Vec<&str> = s.lines().collect();
+
+        let start = match lines.iter().position(|l| l.starts_with("TY  - ")) {
+            Some(start) => start,
+            None => return Ok(RIS(Vec::new())),
+        };
 
-impl PartialEntry {
-    fn new() -> Self {
-        Self {
-            entry: None,
-            state: ParseState::Start,
-        }
+        let end = lines
+            .iter()
+            .rposition(|l| l.starts_with("ER  -"))
+            .filter(|&end| end > start)
+            .unwrap_or(lines.len() - 1);
+
+        lines[start..=end]
+            .join("\n")
+            .parse()
+            .map_err(|mut e: ParseError| {
+                e.line_no += start;
+                e
+            })
     }
 
-    fn parse_line(&mut self, line: &str, line_no: usize) -> Result<ParseState, ParseError> {
-        use ParseErrorKind::*;
-        use ReferenceType::*;
+    /// Count the entries of each reference type.
+    pub fn count_by_type(&self) -> BTreeMap<&ReferenceType, usize> {
+        let mut counts = BTreeMap::new();
 
-        lazy_static! {
-            static ref LINE_RE: Regex = Regex::new("([A-Z][A-Z0-9])  - (.*)").unwrap();
+        for entry in self.0.iter() {
+            *counts.entry(&entry.reference_type).or_insert(0) += 1;
         }
 
-        let matches = LINE_RE
-            .captures(line)
-            .ok_or_else(|| ParseError::new(line_no, InvalidLine))?;
+        counts
+    }
 
-        let key = matches.get(1).unwrap().as_str();
-        let value = matches.get(2).unwrap().as_str();
+    /// The most common reference type in the list, or `None` if the list is empty.
+    ///
+    /// Ties are broken in favour of the type which appears first.
+    pub fn dominant_type(&self) -> Option<ReferenceType> {
+        let counts = self.count_by_type();
+        let max = *counts.values().max()?;
+
+        self.0
+            .iter()
+            .map(|e| &e.reference_type)
+            .find(|t| counts[t] == max)
+            .cloned()
+    }
 
-        match self.state {
-            ParseState::Start => {
-                if key == "TY" {
-                    self.state = ParseState::InProgress;
-                    self.entry = Some(Entry::new(value.parse().unwrap()));
-                } else {
-                    return Err(ParseError::new(line_no, UnterminatedEntry));
-                }
+    /// Serialize the entries into a human-readable string for debugging, with a header line before each entry.
+    ///
+    /// **The output is not valid RIS and can't be parsed back**: it is only meant to make large files easier to eyeball.
+    pub fn to_pretty_string(&self) -> String {
+        let mut s = String::new();
+
+        for (i, entry) in self.0.iter().enumerate() {
+            if i > 0 {
+                s.push('\n');
             }
-            ParseState::InProgress => {
-                let entry = self.entry.as_mut().unwrap();
 
-                match key {
-                    "TY" => return Err(ParseError::new(line_no, UnterminatedEntry)),
+            let reference_type = match entry.reference_type {
+                ReferenceType::Other(ref other) => other.clone(),
+                ref reference_type => format!("{:?}", reference_type),
+            };
+
+            s.push_str(&format!(
+                "# Entry {}: {}\n{}\n",
+                i + 1,
+                reference_type,
+                entry
+            ));
+        }
 
-                    "ID" => set_unique_field(&mut entry.id, value, line_no)?,
+        s
+    }
 
-                    "T1" | "TI" => set_unique_field(&mut entry.title, value, line_no)?,
-                    "T2" => set_unique_field(&mut entry.secondary_title, value, line_no)?,
-                    "T3" => set_unique_field(&mut entry.tertiary_title, value, line_no)?,
+    /// The entries missing some of the [recommended fields](crate::ReferenceType::recommended_fields) for their type,
+    /// as pairs of the index of the entry and the names of the missing fields.
+    pub fn incomplete_entries(&self) -> Vec<(usize, Vec<&'static str>)> {
+        self.0
+            .iter()
+            .map(Entry::missing_recommended_fields)
+            .enumerate()
+            .filter(|(_, missing)| !missing.is_empty())
+            .collect()
+    }
 
-                    "A1" | "AU" => entry.authors.push(String::from(value)),
-                    "A2" | "ED" => entry.secondary_authors.push(String::from(value)),
-                    "A3" => entry.tertiary_authors.push(String::from(value)),
+    /// All the authors of all the entries, deduplicated case-insensitively, in order of first appearance.
+    ///
+    /// The spelling of the first appearance of each author is kept.
+    pub fn unique_authors(&self) -> Vec<&str> {
+        self.author_counts().into_iter().map(|(a, _)| a).collect()
+    }
 
-                    "Y1" | "PY" | "DA" => {
-                        set_unique_field(&mut entry.primary_date, value, line_no)?
-                    }
-                    "Y2" => set_unique_field(&mut entry.secondary_date, value, line_no)?,
-
-                    "N1" => set_unique_field(&mut entry.notes, value, line_no)?,
-
-                    "AB" | "N2" => set_unique_field(&mut entry.abstract_, value, line_no)?,
-                    "KW" => entry.keywords.push(String::from(value)),
-                    "RP" => set_unique_field(&mut entry.reprint, value, line_no)?,
-                    "AV" => set_unique_field(&mut entry.availability, value, line_no)?,
-
-                    "CA" => set_unique_field(&mut entry.caption, value, line_no)?,
-                    "CN" => set_unique_field(&mut entry.call_number, value, line_no)?,
-                    "DO" => set_unique_field(&mut entry.doi, value, line_no)?,
-
-                    "SP" => set_unique_field(&mut entry.start_page, value, line_no)?,
-                    "EP" => set_unique_field(&mut entry.end_page, value, line_no)?,
-
-                    "JF" | "JO" => set_unique_field(&mut entry.journal, value, line_no)?,
-                    "JA" => set_unique_field(&mut entry.journal_abbrev, value, line_no)?,
-                    "J1" => set_unique_field(&mut entry.journal_abbrev_1, value, line_no)?,
-                    "J2" => set_unique_field(&mut entry.journal_abbrev_2, value, line_no)?,
-
-                    "VL" => set_unique_field(&mut entry.volume, value, line_no)?,
-                    "IS" => set_unique_field(&mut entry.issue, value, line_no)?,
-                    "CY" => set_unique_field(&mut entry.city, value, line_no)?,
-                    "PB" => set_unique_field(&mut entry.publisher, value, line_no)?,
-                    "SN" => set_unique_field(&mut entry.serial_number, value, line_no)?,
-                    "AD" => set_unique_field(&mut entry.address, value, line_no)?,
-
-                    "U1" => set_unique_field(&mut entry.user_1, value, line_no)?,
-                    "U2" => set_unique_field(&mut entry.user_2, value, line_no)?,
-                    "U3" => set_unique_field(&mut entry.user_3, value, line_no)?,
-                    "U4" => set_unique_field(&mut entry.user_4, value, line_no)?,
-                    "U5" => set_unique_field(&mut entry.user_5, value, line_no)?,
-
-                    "C1" => set_unique_field(&mut entry.custom_1, value, line_no)?,
-                    "C2" => set_unique_field(&mut entry.custom_2, value, line_no)?,
-                    "C3" => set_unique_field(&mut entry.custom_3, value, line_no)?,
-                    "C4" => set_unique_field(&mut entry.custom_4, value, line_no)?,
-                    "C5" => set_unique_field(&mut entry.custom_5, value, line_no)?,
-                    "C6" => set_unique_field(&mut entry.custom_6, value, line_no)?,
-                    "C7" => set_unique_field(&mut entry.custom_7, value, line_no)?,
-                    "C8" => set_unique_field(&mut entry.custom_8, value, line_no)?,
-
-                    "M1" => set_unique_field(&mut entry.misc_1, value, line_no)?,
-                    "M2" => set_unique_field(&mut entry.misc_2, value, line_no)?,
-                    "M3" => set_unique_field(&mut entry.misc_3, value, line_no)?,
-
-                    "BT" => {
-                        let field = match entry.reference_type {
-                            WholeBook | UnpublishedWork => &mut entry.title,
-                            _ => &mut entry.secondary_title,
-                        };
-
-                        set_unique_field(field, value, line_no)?;
-                    }
+    /// Like [unique_authors](crate::RIS::unique_authors), but also counts how many times each author appears.
+    pub fn author_counts(&self) -> Vec<(&str, usize)> {
+        let mut counts: Vec<(&str, usize)> = Vec::new();
+        let mut indices = HashMap::new();
 
-                    "ER" => {
-                        if value.is_empty() {
-                            self.state = ParseState::End;
-                        } else {
-                            return Err(ParseError::new(line_no, InvalidLine));
-                        }
-                    }
+        for author in self.0.iter().flat_map(|e| e.authors.iter()) {
+            let i = *indices.entry(author.to_lowercase()).or_insert_with(|| {
+                counts.push((author, 0));
+                counts.len() - 1
+            });
 
-                    _ => {
-                        return Err(ParseError::new(line_no, InvalidKey));
-                    }
-                }
+            counts[i].1 += 1;
+        }
+
+        counts
+    }
+
+    /// Export the entries to a JSON array of objects.
+    ///
+    /// Each object contains the `reference_type` of the entry and all its populated fields, keyed by field name.
+    /// Repeatable fields such as `authors` are arrays of strings, and all the other fields are strings.
+    /// Unknown tags, if any, are in an `unknown_tags` object mapping each tag to the array of its values.
+    pub fn to_json(&self) -> String {
+        let mut json = String::from("[");
+
+        for (i, entry) in self.0.iter().enumerate() {
+            if i > 0 {
+                json.push(',');
             }
-            ParseState::End => return Err(ParseError::new(line_no, TagOutsideEntry)),
+
+            entry.write_json(&mut json);
         }
 
-        Ok(self.state)
+        json.push(']');
+        json
     }
-}
 
-#[inline(always)]
-fn set_unique_field<T>(field: &mut Option<T>, value: &str, line_no: usize) -> Result<(), ParseError>
-where
-    T: FromStr,
-    ParseErrorKind: From<T::Err>,
-{
-    if field.is_some() {
-        Err(ParseError::new(line_no, ParseErrorKind::DuplicateField))
-    } else {
-        *field = Some(
-            value
-                .parse()
-                .map_err(|e: T::Err| ParseError::new(line_no, e.into()))?,
-        );
-        Ok(())
+    /// Export the entries to newline-delimited JSON, with one line for each entry, as given by
+    /// [Entry::to_json](crate::Entry::to_json), so the objects are the same as in [to_json](crate::RIS::to_json).
+    ///
+    /// Each line is terminated by `\n`. Newlines inside values are escaped, so they never split an object.
+    pub fn to_ndjson(&self) -> String {
+        self.0.iter().map(|entry| entry.to_json() + "\n").collect()
     }
-}
 
-// FIXME Once `!` is stabilized, this conversion should be unnecessary
-#[doc(hidden)]
-impl From<std::convert::Infallible> for ParseErrorKind {
-    fn from(_: std::convert::Infallible) -> Self {
-        panic!();
+    /// Export the entries to CSV, with a header row followed by one row per entry.
+    ///
+    /// There is one column for each field, named after the field, starting with `reference_type`.
+    /// Repeatable fields such as `authors` are joined with `; ` into a single cell.
+    /// Cells containing commas, double quotes or line breaks are quoted as described in RFC 4180.
+    pub fn to_csv(&self) -> String {
+        let mut csv = Entry::csv_header();
+        csv.push('\n');
+
+        for entry in self.0.iter() {
+            csv.push_str(&entry.csv_row());
+            csv.push('\n');
+        }
+
+        csv
     }
-}
 
-#[doc(hidden)]
-impl From<ParseDateError> for ParseErrorKind {
-    fn from(_: ParseDateError) -> Self {
-        ParseErrorKind::InvalidDate
+    /// [Sort the authors](crate::Entry::sort_authors) of every entry alphabetically.
+    pub fn sort_all_authors(&mut self) {
+        self.0.iter_mut().for_each(Entry::sort_authors);
     }
-}
 
-impl Display for RIS {
-    /// Serializes a slice of entries into a multi-entry RIS string
-    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
-        if let Some(entry) = self.0.first() {
-            entry.fmt(f)?;
+    /// Serialize the entries into a RIS string in the order given by a comparator, without reordering the entries themselves.
+    pub fn to_string_sorted_by(
+        &self,
+        mut compare: impl FnMut(&Entry, &Entry) -> Ordering,
+    ) -> String {
+        let mut entries: Vec<&Entry> = self.0.iter().collect();
+        entries.sort_by(|a, b| compare(a, b));
+
+        let mut s = String::new();
+        write_entries(&mut s, entries, &SerializeOptions::default()).unwrap();
+        s
+    }
+
+    /// Serialize the entries into a RIS string with the given [SerializeOptions](crate::SerializeOptions).
+    pub fn to_string_with_options(&self, options: &SerializeOptions) -> String {
+        let mut s = String::new();
+        write_entries(&mut s, self.0.iter(), options).unwrap();
+        s
+    }
+
+    /// Serialize the entries into a RIS string with the given line ending.
+    pub fn to_string_with_line_ending(&self, line_ending: LineEnding) -> String {
+        self.to_string_with_options(&SerializeOptions {
+            line_ending,
+            ..SerializeOptions::default()
+        })
+    }
 
-            for entry in self.0.iter().skip(1) {
-                writeln!(f)?;
-                entry.fmt(f)?;
+    /// Merge the duplicate entries in the list, keeping the most complete record of each.
+    ///
+    /// Two entries are considered duplicates if they have the same [normalized DOI](crate::Entry::normalized_doi),
+    /// compared case-insensitively, or, if neither has a DOI, the same [normalized title](crate::Entry::normalized_title)
+    /// and year of publication. Empty DOIs count as missing. Entries with neither a DOI nor a title are never considered duplicates.
+    ///
+    /// Among each group of duplicates, the entry with the highest [completeness](crate::Entry::completeness) is kept,
+    /// with ties broken by [field count](crate::Entry::field_count) and then by order of appearance.
+    /// Its missing fields are then [filled](crate::Entry::fill_missing_from) from the other duplicates, in order.
+    /// The merged entry takes the place of the first entry of its group.
+    ///
+    /// Returns a report of every merge performed, with indices into the list as it was before merging.
+    pub fn dedup_keep_most_complete(&mut self) -> Vec<DedupMerge> {
+        let mut groups: Vec<Vec<usize>> = Vec::new();
+        let mut indices = HashMap::new();
+
+        for (i, entry) in self.0.iter().enumerate() {
+            match entry.dedup_key() {
+                Some(key) => {
+                    let group = *indices.entry(key).or_insert_with(|| {
+                        groups.push(Vec::new());
+                        groups.len() - 1
+                    });
+
+                    groups[group].push(i);
+                }
+                None => groups.push(vec![i]),
             }
         }
 
-        Ok(())
+        let mut entries: Vec<Option<Entry>> = self.0.drain(..).map(Some).collect();
+        let mut merges = Vec::new();
+
+        for group in groups {
+            // `max_by` returns the last of equal elements, so iterate in reverse to prefer the first one
+            let kept = group
+                .iter()
+                .copied()
+                .rev()
+                .max_by(|&a, &b| {
+                    let (a, b) = (entries[a].as_ref().unwrap(), entries[b].as_ref().unwrap());
+
+                    a.completeness()
+                        .partial_cmp(&b.completeness())
+                        .unwrap_or(Ordering::Equal)
+                        .then_with(|| a.field_count().cmp(&b.field_count()))
+                })
+                .unwrap();
+
+            let mut entry = entries[kept].take().unwrap();
+            let removed: Vec<usize> = group.into_iter().filter(|&i| i != kept).collect();
+
+            for &i in removed.iter() {
+                entry.fill_missing_from(entries[i].as_ref().unwrap());
+            }
+
+            if !removed.is_empty() {
+                merges.push(DedupMerge { kept, removed });
+            }
+
+            self.0.push(entry);
+        }
+
+        merges
     }
-}
 
-/// A single entry in the RIS file, started by a `TY` and terminated by a `ER`.
-///
-/// This type implements [Display](std::fmt::Display) and [FromStr](std::str::FromStr) to (de)serialize to/from strings.
-/// To (de)serialize files composed of multiple records, see [RIS](crate::RIS)
-///
-/// # Field mappings
-///
-/// All fields except `TY` are optional, and are stored as `Option`s.
-/// Repeated fields are invalid, and will cause a [ParseError](crate::ParseError).
-///
-/// [ReferenceType]: crate::ReferenceType
-/// [String]: std::string::String
-/// [PublicationDate]: crate::PublicationDate
-///
-/// | Key  | Field              | Type              |
-/// |------|--------------------|-------------------|
-/// | `TY` | `reference_type`   | [ReferenceType]   |
-/// | `ID` | `id`               | [String]          |
-/// | `T1` | `title`            | [String]          |
-/// | `T2` | `secondary_title`  | [String]          |
-/// | `T3` | `tertiary_title`   | [String]          |
-/// | `Y1` | `primary_date`     | [PublicationDate] |
-/// | `Y2` | `secondary_date`   | [PublicationDate] |
-/// | `N1` | `notes`            | [String]          |
-/// | `N2` | `abstract_`        | [String]          |
-/// | `RP` | `reprint`          | [String]          |
-/// | `AV` | `availability`     | [String]          |
-/// | `CA` | `caption`          | [String]          |
-/// | `CN` | `call_number`      | [String]          |
-/// | `DO` | `doi`              | [String]          |
-/// | `SP` | `start_page`       | [String]          |
-/// | `EP` | `end_page`         | [String]          |
-/// | `JA` | `journal_abbrev`   | [String]          |
-/// | `J1` | `journal_abbrev_1` | [String]          |
-/// | `J2` | `journal_abbrev_2` | [String]          |
-/// | `VL` | `volume`           | [String]          |
-/// | `IS` | `issue`            | [String]          |
-/// | `CY` | `city`             | [String]          |
-/// | `PB` | `publisher`        | [String]          |
-/// | `SN` | `serial_number`    | [String]          |
-/// | `AD` | `address`          | [String]          |
-/// | `U1` | `user_1`           | [String]          |
-/// | `U2` | `user_2`           | [String]          |
-/// | `U3` | `user_3`           | [String]          |
-/// | `U4` | `user_4`           | [String]          |
-/// | `U5` | `user_5`           | [String]          |
-/// | `C1` | `custom_1`         | [String]          |
-/// | `C2` | `custom_2`         | [String]          |
-/// | `C3` | `custom_3`         | [String]          |
-/// | `C4` | `custom_4`         | [String]          |
-/// | `C5` | `custom_5`         | [String]          |
-/// | `M1` | `misc_1`           | [String]          |
-/// | `M2` | `misc_2`           | [String]          |
-/// | `M3` | `misc_3`           | [String]          |
-///
-/// Some fields are `Vec`s, and the corresponding keys are allowed to appear multiple times:
-///
-/// | Key  | Field              | Type     |
-/// |------|--------------------|----------|
-/// | `A1` | `authors`          | [String] |
-/// | `A2` | `second_authors`   | [String] |
-/// | `A3` | `tertiary_authors` | [String] |
-/// | `KW` | `keywords`         | [String] |
-///
-/// # Field oddities
-///
-/// **Note that the following behaviours are inconsistently documented and I am by no means a bibliography expert.**
-/// **I wasn't able to find authoritative, conclusive documentation on how to handle them in a standard manner, so this is my best attempt at it.**
-/// **Any help hunting down information would be appreciated.**
-///
-/// During parsing, some keys are considered synonims and mapped to a common field:
-///
-/// | Key  | Synonims   | Field             |
-/// |------|------------|-------------------|
-/// | `T1` | `TI`       | `title`           |
-/// | `A1` | `AU`       | `first_authors`   |
-/// | `A2` | `ED`       | `second_authors`  |
-/// | `T2` | `JF`, `JO` | `secondary_title` |
-/// | `Y1` | `PY`, `DA` | `primary_date`    |
-/// | `N2` | `AB`       | `abstract_`       |
-///
-/// Some synonims are mapped conditionally depending on the reference type `TY`:
-///
-/// | `TY`               | Key  | Synonims | Field             |
-/// |--------------------|------|----------|-------------------|
-/// | `Whole Book`       | `T1` | `BT`     | `title`           |
-/// | `Unpublished Work` | `T2` | `BT`     | `secondary_title` |
-/// | `Unpublished Work` | `T1` | `CT`     | `title`           |
-///
-/// Some bibliography systems may resolve a journal abbreviation (`JA/J2`) as a standard abbreviated name for a journal, and automatically populate `T2` with the full journal name.
-/// This behaviour is not implemented as I could only find inconsistent documentation for it.
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
-pub struct Entry {
-    pub reference_type: ReferenceType, // TY
+    /// Build a list from anything convertible to entries, such as [EntryBuilder](crate::EntryBuilder)s.
+    pub fn from_entries<I, E>(entries: I) -> RIS
+    where
+        I: IntoIterator<Item = E>,
+        E: Into<Entry>,
+    {
+        entries.into_iter().map(Into::into).collect()
+    }
 
-    pub id: Option<String>, // ID
+    /// Remove the [empty](crate::Entry::is_empty) entries, which only have a reference type,
+    /// such as the stubs produced by some malformed exports.
+    ///
+    /// Returns the number of entries removed.
+    pub fn remove_empty(&mut self) -> usize {
+        let len = self.0.len();
+        self.0.retain(|entry| !entry.is_empty());
+        len - self.0.len()
+    }
 
-    pub title: Option<String>,           // T1, TI
-    pub secondary_title: Option<String>, // T2
-    pub tertiary_title: Option<String>,  // T3
+    /// Set the `id` of every entry to its [citation key](crate::Entry::cite_key), replacing any existing `id`.
+    ///
+    /// When several entries have the same key, all of them are disambiguated by appending a suffix in order of
+    /// appearance: `a`, `b`, ..., `z`, then `aa`, `ab` and so on, e.g. `shannon1948a` and `shannon1948b`.
+    pub fn assign_unique_cite_keys(&mut self) {
+        let keys: Vec<String> = self.0.iter().map(Entry::cite_key).collect();
 
-    pub authors: Vec<String>,           // AU, A1
-    pub secondary_authors: Vec<String>, // A2, ED
-    pub tertiary_authors: Vec<String>,  // A3
+        let mut counts = HashMap::new();
+        for key in keys.iter() {
+            *counts.entry(key.as_str()).or_insert(0) += 1;
+        }
 
-    pub primary_date: Option<PublicationDate>, // PY, Y1, DA
-    pub secondary_date: Option<PublicationDate>, // Y2
+        let mut suffixes = HashMap::new();
 
-    pub notes: Option<String>, // N1
+        for (entry, key) in self.0.iter_mut().zip(keys.iter()) {
+            let id = if counts[key.as_str()] > 1 {
+                let n = suffixes.entry(key.as_str()).or_insert(0);
+                *n += 1;
+                format!("{}{}", key, alphabetic_suffix(*n))
+            } else {
+                key.clone()
+            };
 
-    pub abstract_: Option<String>,    // AB, N2
-    pub keywords: Vec<String>,        // KW
-    pub reprint: Option<String>,      // RP
-    pub availability: Option<String>, // AV
-    pub caption: Option<String>,      // CA
-    pub call_number: Option<String>,  // CN
-    pub doi: Option<String>,          // DO
+            entry.id = Some(id);
+        }
+    }
 
-    pub start_page: Option<String>, // SP
-    pub end_page: Option<String>,   // EP
+    /// Sort the entries chronologically, with the entries without a date last.
+    ///
+    /// The sort is stable, see [Entry::cmp_by_date](crate::Entry::cmp_by_date) for details.
+    pub fn sort_by_date(&mut self) {
+        self.0.sort_by(Entry::cmp_by_date);
+    }
 
-    pub journal: Option<String>,          // JF, JO
-    pub journal_abbrev: Option<String>,   // JA
-    pub journal_abbrev_1: Option<String>, // J1
-    pub journal_abbrev_2: Option<String>, // J2
+    /// Sort the entries in the order of an external list of keys, such as the DOIs or citation keys of
+    /// an in-text citation order, with `key_fn` giving the key of each entry.
+    ///
+    /// Entries whose key isn't in the list, or which have no key, are placed at the end.
+    /// The sort is stable, so entries with the same key and unmatched entries keep their relative order.
+    /// Keys are compared exactly: normalize them in `key_fn` and in the list to match them loosely.
+    pub fn reorder_by(&mut self, keys: &[String], key_fn: impl Fn(&Entry) -> Option<String>) {
+        let mut positions = HashMap::new();
+
+        for (i, key) in keys.iter().enumerate() {
+            positions.entry(key.as_str()).or_insert(i);
+        }
 
-    pub volume: Option<String>,        // VL
-    pub issue: Option<String>,         // IS
-    pub city: Option<String>,          // CY
-    pub publisher: Option<String>,     // PB
-    pub serial_number: Option<String>, // SN
-    pub address: Option<String>,       // AD
+        self.0.sort_by_cached_key(|entry| {
+            key_fn(entry)
+                .and_then(|key| positions.get(key.as_str()).copied())
+                .unwrap_or(keys.len())
+        });
+    }
 
-    pub user_1: Option<String>, // U1
-    pub user_2: Option<String>, // U2
-    pub user_3: Option<String>, // U3
-    pub user_4: Option<String>, // U4
-    pub user_5: Option<String>, // U5
+    /// Count how many entries have each field populated, for profiling large lists.
+    ///
+    /// Every field other than `reference_type` and `unknown_tags` is included, even if no entry has it.
+    /// Repeatable fields such as `authors` count once per entry, regardless of how many values they hold.
+    pub fn field_presence(&self) -> BTreeMap<&'static str, usize> {
+        let mut presence: BTreeMap<&'static str, usize> = Entry::new(ReferenceType::Generic)
+            .fields()
+            .into_iter()
+            .map(|(name, _)| (name, 0))
+            .collect();
+
+        for entry in self.0.iter() {
+            for (name, value) in entry.fields() {
+                if value.is_set() {
+                    *presence.get_mut(name).unwrap() += 1;
+                }
+            }
+        }
 
-    pub custom_1: Option<String>, // C1
-    pub custom_2: Option<String>, // C2
-    pub custom_3: Option<String>, // C3
-    pub custom_4: Option<String>, // C4
-    pub custom_5: Option<String>, // C5
-    pub custom_6: Option<String>, // C5
-    pub custom_7: Option<String>, // C5
-    pub custom_8: Option<String>, // C5
+        presence
+    }
 
-    pub misc_1: Option<String>, // M1
-    pub misc_2: Option<String>, // M2
-    pub misc_3: Option<String>, // M3
-}
+    /// Move all the entries of `other` to the end of this RIS, leaving `other` empty, as with [Vec::append].
+    ///
+    /// Entries are moved as they are, without any deduplication:
+    /// see [dedup_keep_most_complete](crate::RIS::dedup_keep_most_complete) to merge duplicates afterwards.
+    pub fn append(&mut self, other: &mut RIS) {
+        self.0.append(&mut other.0);
+    }
 
-impl Entry {
-    pub fn new(reference_type: ReferenceType) -> Self {
-        Self {
-            reference_type,
+    /// [Canonicalize](crate::ReferenceType::canonicalize) the reference types of all the entries,
+    /// converting values such as `Other("jour ")` to standard variants where possible.
+    pub fn canonicalize_types(&mut self) {
+        for entry in &mut self.0 {
+            entry.reference_type.canonicalize();
+        }
+    }
 
-            id: None,
+    /// Guess which bibliography manager exported a RIS file, to apply cleanups specific to it.
+    ///
+    /// This is only a diagnostic based on the tags that each tool tends to use, and should not be relied upon:
+    /// tools change their export format over time, and files may have been edited by hand or by other tools.
+    /// The first of the following rules which matches is used:
+    ///
+    /// 1. [PubMed](crate::Exporter::PubMed): a `DB` tag with `PubMed` or `MEDLINE`,
+    ///    or a `UR` tag linking to `pubmed.ncbi.nlm.nih.gov`
+    /// 2. [EndNote](crate::Exporter::EndNote): an `LB` (label) tag, or a byte order mark followed by `\r\n` line endings
+    /// 3. [Zotero](crate::Exporter::Zotero): a `DP` tag, or both `TI` and `DA` tags
+    /// 4. [Mendeley](crate::Exporter::Mendeley): both `T1` and `Y1` tags, without any `DA` tag
+    ///
+    /// If no rule matches, `None` is returned. The file doesn't need to be valid RIS: lines which aren't tags are ignored.
+    pub fn detect_exporter(s: &str) -> Option<Exporter> {
+        let has_bom = s.starts_with('\u{FEFF}');
+        let has_crlf = s.contains("\r\n");
+        let tags: Vec<_> = trim_bom(s)
+            .lines()
+            .filter_map(|line| split_line(trim_form_feeds(line)))
+            .collect();
+        let has_tag = |tag: &str| tags.iter().any(|(key, _)| *key == tag);
+        let has_value = |tag: &str, f: &dyn Fn(&str) -> bool| {
+            tags.iter().any(|(key, value)| *key == tag && f(value))
+        };
 
-            title: None,
-            secondary_title: None,
-            tertiary_title: None,
+        if has_value("DB", &|value| {
+            value.eq_ignore_ascii_case("PubMed") || value.eq_ignore_ascii_case("MEDLINE")
+        }) || has_value("UR", &|value| value.contains("pubmed.ncbi.nlm.nih.gov"))
+        {
+            Some(Exporter::PubMed)
+        } else if has_tag("LB") || (has_bom && has_crlf) {
+            Some(Exporter::EndNote)
+        } else if has_tag("DP") || (has_tag("TI") && has_tag("DA")) {
+            Some(Exporter::Zotero)
+        } else if has_tag("T1") && has_tag("Y1") && !has_tag("DA") {
+            Some(Exporter::Mendeley)
+        } else {
+            None
+        }
+    }
 
-            authors: Vec::new(),
-            secondary_authors: Vec::new(),
-            tertiary_authors: Vec::new(),
+    /// A new RIS with clones of the entries matching a predicate, in order.
+    pub fn filter(&self, predicate: impl Fn(&Entry) -> bool) -> RIS {
+        RIS(self
+            .0
+            .iter()
+            .filter(|entry| predicate(entry))
+            .cloned()
+            .collect())
+    }
 
-            primary_date: None,
-            secondary_date: None,
+    /// Keep only the entries matching a predicate, in order, removing all the others.
+    pub fn retain(&mut self, predicate: impl FnMut(&Entry) -> bool) {
+        self.0.retain(predicate);
+    }
 
-            notes: None,
+    /// The citations between the entries of this RIS, found by matching the `cited_references` of each entry against
+    /// the other entries, as a map from the index of each citing entry to the sorted indices of the entries it cites.
+    ///
+    /// A cited reference matches an entry if either:
+    /// - it contains a DOI equal to the entry's [DOI](crate::Entry::normalized_doi), ignoring case
+    /// - it contains both the entry's [normalized title](crate::Entry::normalized_title) once normalized the same way,
+    ///   and the year of its `primary_date`
+    ///
+    /// DOI matches are precise, but title matches can miss references with abbreviated or misspelled titles,
+    /// and can wrongly match entries with short, generic titles published the same year.
+    /// Formats which don't include titles in cited references, such as Web of Science, only match by DOI.
+    /// Entries never cite themselves, and entries which cite no other entry are not in the map.
+    pub fn build_citation_graph(&self) -> HashMap<usize, Vec<usize>> {
+        let keys: Vec<_> = self
+            .0
+            .iter()
+            .map(|entry| {
+                let doi = entry
+                    .normalized_doi()
+                    .filter(|doi| !doi.is_empty())
+                    .map(str::to_lowercase);
+                let title = entry
+                    .normalized_title()
+                    .filter(|title| !title.is_empty())
+                    .zip(
+                        entry
+                            .primary_date
+                            .as_ref()
+                            .map(|date| date.year.to_string()),
+                    );
+
+                (doi, title)
+            })
+            .collect();
+
+        let mut graph = HashMap::new();
+
+        for (i, entry) in self.0.iter().enumerate() {
+            let mut cited = Vec::new();
+
+            for reference in &entry.cited_references {
+                let reference_doi = find_doi(reference).map(str::to_lowercase);
+                let normalized_reference = normalize_text(reference);
+
+                for (j, (doi, title)) in keys.iter().enumerate() {
+                    let doi_matches = doi.is_some() && *doi == reference_doi;
+                    let title_matches = title.as_ref().is_some_and(|(title, year)| {
+                        normalized_reference.contains(title.as_str())
+                            && reference.contains(year.as_str())
+                    });
+
+                    if j != i && (doi_matches || title_matches) && !cited.contains(&j) {
+                        cited.push(j);
+                    }
+                }
+            }
 
-            abstract_: None,
-            keywords: Vec::new(),
-            reprint: None,
-            availability: None,
-            caption: None,
-            call_number: None,
-            doi: None,
+            if !cited.is_empty() {
+                cited.sort_unstable();
+                graph.insert(i, cited);
+            }
+        }
 
-            start_page: None,
-            end_page: None,
+        graph
+    }
+}
 
-            journal_abbrev: None,
-            journal: None,
-            journal_abbrev_1: None,
-            journal_abbrev_2: None,
+/// A merge of duplicate entries performed by [RIS::dedup_keep_most_complete](crate::RIS::dedup_keep_most_complete).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct DedupMerge {
+    /// The index of the entry which was kept.
+    pub kept: usize,
+    /// The indices of the duplicates which were merged into the kept entry and removed.
+    pub removed: Vec<usize>,
+}
 
-            volume: None,
-            issue: None,
-            city: None,
-            publisher: None,
-            serial_number: None,
-            address: None,
+impl FromIterator<Entry> for RIS {
+    fn from_iter<I: IntoIterator<Item = Entry>>(iter: I) -> Self {
+        RIS(iter.into_iter().collect())
+    }
+}
 
-            user_1: None,
-            user_2: None,
-            user_3: None,
-            user_4: None,
-            user_5: None,
+impl FromStr for RIS {
+    type Err = ParseError;
 
-            custom_1: None,
-            custom_2: None,
-            custom_3: None,
-            custom_4: None,
-            custom_5: None,
-            custom_6: None,
-            custom_7: None,
-            custom_8: None,
+    /// Parse a RIS file from a string.
+    /// See [Entry](crate::Entry) for more information on how keys are mapped to fields.
+    fn from_str(s: &str) -> Result<RIS, Self::Err> {
+        RIS::from_str_with_options(s, &ParseOptions::default())
+    }
+}
 
-            misc_1: None,
-            misc_2: None,
-            misc_3: None,
+/// Options to make parsing more lenient towards files which don't strictly follow the RIS format.
+///
+/// The default options parse strictly.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ParseOptions {
+    /// Treat lines which don't start with a tag as a continuation of the previous field's value.
+    ///
+    /// Continuation lines are joined to the value with a newline, after trimming their leading whitespace,
+    /// since some files indent them to align with the value of the first line.
+    ///
+    /// Only lines starting with a recognized tag start a new field, so that a continuation line which happens to look
+    /// like a tag, such as `DN  - sequence`, is still treated as text.
+    /// If [preserve_unknown_tags](crate::ParseOptions::preserve_unknown_tags) is also set, any well-formed tag starts
+    /// a new field instead, since unknown tags can't be told apart from text.
+    ///
+    /// Lines are never joined to `TY` or `ER`. Blank lines are ignored, unless they are followed by
+    /// a continuation line of the same value, so that they don't end up at the end of values.
+    pub multiline_values: bool,
+    /// When joining continuation lines, rejoin words hyphenated across the line break,
+    /// so that `word-\ncontinuation` becomes `wordcontinuation`.
+    ///
+    /// This has no effect unless `multiline_values` is also set.
+    /// Note that this also joins legitimately hyphenated compounds which happen to be split across lines.
+    pub join_hyphenated_lines: bool,
+    /// How to handle entries with more than one of the `Y1`, `PY` and `DA` tags, which all map to `primary_date`.
+    pub primary_date_conflicts: DateConflictPolicy,
+    /// Store well-formed but unrecognized tags in [unknown_tags](crate::Entry::unknown_tags) instead of failing.
+    ///
+    /// Lines which don't match the `<letter><letter_or_number>  - <value>` format are still invalid.
+    pub preserve_unknown_tags: bool,
+    /// Parse `N2` as a second notes field instead of as the abstract.
+    ///
+    /// `N2` is documented as the abstract in most specifications, and is parsed as such by default,
+    /// but some dialects use it for additional notes.
+    /// When this is set, `N2` values are appended to [notes](crate::Entry::notes) after the `N1` value,
+    /// separated by newlines, and only `AB` is parsed as the abstract. Repeated `N1` tags are still invalid.
+    pub n2_as_notes: bool,
+    /// Keep the last entry if the input ends before its `ER` tag, as in truncated files,
+    /// instead of failing with an [UnterminatedEntry](crate::ParseErrorKind::UnterminatedEntry) error.
+    ///
+    /// The entry is kept as it is, with the fields parsed up to the end of input.
+    pub finalize_at_eof: bool,
+    /// Append the values of repeated tags which map to a single text field to the first value, separated by a space,
+    /// instead of failing with a [DuplicateField](crate::ParseErrorKind::DuplicateField) error.
+    ///
+    /// This is meant for files which split a long value such as an abstract across several identical tags.
+    /// Synonym tags are also concatenated, so `AB` followed by `N2` are joined too.
+    /// Repeated dates are not concatenated: see [primary_date_conflicts](crate::ParseOptions::primary_date_conflicts).
+    pub concat_repeated_unique_tags: bool,
+    /// Split `AU` and `A1` values on semicolons into several authors, for generators which put all the authors
+    /// on a single line, such as `Shannon, Claude E.; Weaver, Warren`.
+    ///
+    /// Whitespace around each author is trimmed, and empty authors are dropped.
+    /// This is off by default, since a single author could legitimately contain a semicolon.
+    pub split_authors_on_semicolon: bool,
+    /// Drop entries which have no fields other than their type, such as `TY  - GEN\nER  - `,
+    /// instead of including them in the result.
+    ///
+    /// Such entries are valid RIS, and are kept by default. See [is_empty](crate::Entry::is_empty).
+    pub skip_empty_entries: bool,
+    /// Accept fields before the `TY` tag of an entry, as written by some broken exporters (e.g. `ID` before `TY`),
+    /// instead of failing with a [TagOutsideEntry](crate::ParseErrorKind::TagOutsideEntry) error.
+    ///
+    /// This is nonstandard: the fields are kept until the next `TY`, and then applied to its entry in order.
+    /// Fields which are not followed by any `TY` are still an error.
+    pub allow_ty_anywhere: bool,
+    /// Skip repeatable tags with an empty or blank value, such as `KW  - `, instead of adding an empty string
+    /// to the corresponding list, such as `keywords`.
+    ///
+    /// Empty values are added by default. Empty `unknown_tags` are always kept.
+    pub skip_empty_repeated: bool,
+    /// Parse `M3` as the DOI if its value is a DOI, possibly URL-prefixed, and the entry has no `DO` tag
+    /// or an empty one, since some exporters put the DOI in `M3` while others put the type of article there.
+    ///
+    /// This is resolved at the end of each entry, so a `DO` tag always takes precedence, wherever it appears,
+    /// and `M3` is then kept in [misc_3](crate::Entry::misc_3). Other `M3` values are parsed as `misc_3`, as by default.
+    pub m3_doi_as_doi: bool,
+    /// Standard tags to parse nonstandard tags as, such as `XA` as `AU`, for proprietary dialects.
+    /// See [with_tag_aliases](crate::ParseOptions::with_tag_aliases).
+    pub tag_aliases: HashMap<String, String>,
+}
+
+impl ParseOptions {
+    /// The default options, with nonstandard tags parsed as the standard tags they are mapped to.
+    ///
+    /// Aliases are only looked up for tags which aren't recognized, so standard tags can't be redefined.
+    /// An alias to anything other than a recognized tag, or to `TY` or `ER`, is an
+    /// [InvalidKey](crate::ParseErrorKind::InvalidKey) error when the aliased tag is found.
+    ///
+    /// ```
+    /// use std::collections::HashMap;
+    /// use ris::{ParseOptions, RIS};
+    ///
+    /// let mut aliases = HashMap::new();
+    /// aliases.insert(String::from("XA"), String::from("AU"));
+    ///
+    /// let ris = RIS::from_str_with_options(
+    ///     "TY  - JOUR\nXA  - Shannon, Claude E.\nER  - ",
+    ///     &ParseOptions::with_tag_aliases(aliases),
+    /// )
+    /// .unwrap();
+    ///
+    /// assert_eq!(ris.0[0].authors, vec!["Shannon, Claude E."]);
+    /// ```
+    pub fn with_tag_aliases(aliases: HashMap<String, String>) -> Self {
+        ParseOptions {
+            tag_aliases: aliases,
+            ..ParseOptions::default()
         }
     }
 }
 
-impl FromStr for Entry {
-    type Err = ParseError;
+/// A bibliography manager which exports RIS files, as guessed by [detect_exporter](crate::RIS::detect_exporter).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Exporter {
+    /// [Zotero](https://www.zotero.org/)
+    Zotero,
+    /// [Mendeley](https://www.mendeley.com/)
+    Mendeley,
+    /// [EndNote](https://endnote.com/)
+    EndNote,
+    /// [PubMed](https://pubmed.ncbi.nlm.nih.gov/), or tools exporting PubMed records to RIS
+    PubMed,
+}
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut partial = PartialEntry::new();
-        let mut line_no = 0;
+/// How to handle an entry which sets its `primary_date` more than once, for example with both `PY` and `Y1`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum DateConflictPolicy {
+    /// Fail with a [DuplicateField](crate::ParseErrorKind::DuplicateField) error. This is the default.
+    #[default]
+    Error,
+    /// Keep the date which appeared first, ignoring the others.
+    KeepFirst,
+    /// Merge compatible dates, keeping the most specific information from each of them.
+    ///
+    /// Dates are compatible if all the components present in both of them are equal, such as `1948` and `1948/07//`.
+    /// Incompatible dates fail with a [DuplicateField](crate::ParseErrorKind::DuplicateField) error.
+    Merge,
+}
 
-        for line in s.lines() {
-            line_no += 1;
-            partial.parse_line(line, line_no)?;
-        }
+lazy_static! {
+    static ref LINE_RE: Regex = Regex::new("(?s)^([A-Z][A-Z0-9])  - (.*)").unwrap();
+}
 
-        if partial.state == ParseState::End {
-            Ok(partial.entry.unwrap())
-        } else {
-            Err(ParseError::new(line_no, ParseErrorKind::UnterminatedEntry))
+/// An iterator over the entries of a RIS file read from a [BufRead](std::io::BufRead).
+///
+/// Entries are parsed one at a time as they are read, so that the whole file doesn't have to be kept in memory.
+/// The iterator stops after the first error.
+pub struct EntryReader<R> {
+    inner: EntryIter<io::Lines<R>, ReadError>,
+}
+
+impl<R: BufRead> EntryReader<R> {
+    /// Read entries from a reader with the default [ParseOptions](crate::ParseOptions).
+    pub fn new(reader: R) -> Self {
+        Self::with_options(reader, ParseOptions::default())
+    }
+
+    /// Read entries from a reader with the given [ParseOptions](crate::ParseOptions).
+    pub fn with_options(reader: R, options: ParseOptions) -> Self {
+        Self {
+            inner: EntryIter::new(reader.lines(), options),
         }
     }
 }
 
-impl Display for Entry {
-    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
-        writeln!(f, "TY  - {}", &self.reference_type)?;
-
-        write_tag(f, "ID", &self.id)?;
-
-        write_tag(f, "T1", &self.title)?;
-        write_tag(f, "T2", &self.secondary_title)?;
-        write_tag(f, "T3", &self.tertiary_title)?;
-
-        write_tags(f, "A1", &self.authors)?;
-        write_tags(f, "A2", &self.secondary_authors)?;
-        write_tags(f, "A3", &self.tertiary_authors)?;
-
-        write_tag(f, "Y1", &self.primary_date)?;
-        write_tag(f, "Y2", &self.secondary_date)?;
-
-        write_tag(f, "N1", &self.notes)?;
-        write_tag(f, "AB", &self.abstract_)?;
-
-        write_tags(f, "KW", &self.keywords)?;
-
-        write_tag(f, "RP", &self.reprint)?;
-        write_tag(f, "AV", &self.availability)?;
-        write_tag(f, "CA", &self.caption)?;
-        write_tag(f, "CN", &self.call_number)?;
-        write_tag(f, "DO", &self.doi)?;
-
-        write_tag(f, "SP", &self.start_page)?;
-        write_tag(f, "EP", &self.end_page)?;
-
-        write_tag(f, "JF", &self.journal)?;
-        write_tag(f, "JA", &self.journal_abbrev)?;
-        write_tag(f, "J1", &self.journal_abbrev_1)?;
-        write_tag(f, "J2", &self.journal_abbrev_2)?;
-
-        write_tag(f, "VL", &self.volume)?;
-        write_tag(f, "IS", &self.issue)?;
-        write_tag(f, "CY", &self.city)?;
-        write_tag(f, "PB", &self.publisher)?;
-        write_tag(f, "SN", &self.serial_number)?;
-        write_tag(f, "AD", &self.address)?;
-
-        write_tag(f, "U1", &self.user_1)?;
-        write_tag(f, "U2", &self.user_2)?;
-        write_tag(f, "U3", &self.user_3)?;
-        write_tag(f, "U4", &self.user_4)?;
-        write_tag(f, "U5", &self.user_5)?;
-
-        write_tag(f, "U1", &self.custom_1)?;
-        write_tag(f, "U2", &self.custom_2)?;
-        write_tag(f, "U3", &self.custom_3)?;
-        write_tag(f, "U4", &self.custom_4)?;
-        write_tag(f, "U5", &self.custom_5)?;
-        write_tag(f, "U6", &self.custom_6)?;
-        write_tag(f, "U7", &self.custom_7)?;
-        write_tag(f, "U8", &self.custom_8)?;
-
-        write_tag(f, "M1", &self.misc_1)?;
-        write_tag(f, "M2", &self.misc_2)?;
-        write_tag(f, "M3", &self.misc_3)?;
+impl<R: BufRead> Iterator for EntryReader<R> {
+    type Item = Result<Entry, ReadError>;
 
-        write!(f, "ER  - ")?;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next()
+    }
+}
 
-        Ok(())
+/// Parses entries from an iterator over lines, joining continuation lines if multi-line values are enabled.
+struct EntryIter<I, E> {
+    lines: I,
+    options: ParseOptions,
+    line_no: usize,
+    /// The last tag line read, waiting to see whether the following lines are continuations of it
+    pending: Option<(usize, String)>,
+    /// The number of blank lines read since the pending line, which are only kept if a continuation line follows them
+    blank_lines: usize,
+    done: bool,
+    _error: PhantomData<E>,
+}
+
+impl<'a> EntryIter<Box<dyn Iterator<Item = Result<String, ParseError>> + 'a>, ParseError> {
+    fn from_str(s: &'a str, options: ParseOptions) -> Self {
+        Self::new(Box::new(s.lines().map(|l| Ok(String::from(l)))), options)
     }
 }
 
-#[inline(always)]
-fn write_tag<T: Display>(f: &mut Formatter, tag: &str, field: &Option<T>) -> fmt::Result {
-    if let Some(ref value) = field {
-        writeln!(f, "{}  - {}", tag, value)?;
+impl<I, E, L> EntryIter<I, E>
+where
+    I: Iterator<Item = Result<String, L>>,
+    E: From<L> + From<ParseError>,
+{
+    fn new(lines: I, options: ParseOptions) -> Self {
+        Self {
+            lines,
+            options,
+            line_no: 0,
+            pending: None,
+            blank_lines: 0,
+            done: false,
+            _error: PhantomData,
+        }
     }
 
-    Ok(())
+    /// The next line, paired with its line number (starting at 1).
+    ///
+    /// If multi-line values are enabled, continuation lines are joined to the preceding tag line,
+    /// which keeps its own line number.
+    fn next_line(&mut self) -> Option<Result<(usize, String), E>> {
+        loop {
+            let mut line = match self.lines.next() {
+                Some(Ok(line)) => line,
+                Some(Err(e)) => return Some(Err(e.into())),
+                None => return self.pending.take().map(Ok),
+            };
+
+            self.line_no += 1;
+
+            if self.line_no == 1 && line.starts_with('\u{FEFF}') {
+                line.remove(0);
+            }
+
+            if !self.options.multiline_values {
+                return Some(Ok((self.line_no, line)));
+            }
+
+            let starts_field = split_line(&line).is_some_and(|(key, _)| {
+                self.options.preserve_unknown_tags
+                    || KNOWN_TAGS.contains(&key)
+                    || self.options.tag_aliases.contains_key(key)
+            });
+
+            if !starts_field {
+                if line.trim().is_empty() {
+                    self.blank_lines += 1;
+                    continue;
+                }
+
+                if let Some((_, previous)) = self.pending.as_mut().filter(|(_, l)| {
+                    split_line(l).is_some_and(|(key, _)| key != "TY" && key != "ER")
+                }) {
+                    if self.blank_lines == 0
+                        && self.options.join_hyphenated_lines
+                        && ends_with_hyphenated_word(previous)
+                    {
+                        previous.pop();
+                    } else {
+                        for _ in 0..self.blank_lines {
+                            previous.push('\n');
+                        }
+
+                        previous.push('\n');
+                    }
+
+                    previous.push_str(line.trim_start());
+                    self.blank_lines = 0;
+                    continue;
+                }
+            }
+
+            self.blank_lines = 0;
+
+            if let Some(previous) = self.pending.replace((self.line_no, line)) {
+                return Some(Ok(previous));
+            }
+        }
+    }
 }
 
-#[inline(always)]
-fn write_tags<T: Display>(f: &mut Formatter, tag: &str, field: &[T]) -> fmt::Result {
-    for value in field.iter() {
-        writeln!(f, "{}  - {}", tag, value)?;
+impl<I, E, L> Iterator for EntryIter<I, E>
+where
+    I: Iterator<Item = Result<String, L>>,
+    E: From<L> + From<ParseError>,
+{
+    type Item = Result<Entry, E>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        let mut partial = PartialEntry::new();
+
+        let result = loop {
+            match self.next_line() {
+                Some(Ok((line_no, line))) => {
+                    match partial.parse_line(&line, line_no, &self.options) {
+                        Ok(ParseState::End) => {
+                            let entry = partial.entry.take().unwrap();
+
+                            if self.options.skip_empty_entries && entry.is_empty() {
+                                partial = PartialEntry::new();
+                            } else {
+                                return Some(Ok(entry));
+                            }
+                        }
+                        Ok(_) => {}
+                        Err(e) => break Some(Err(e.into())),
+                    }
+                }
+                Some(Err(e)) => break Some(Err(e)),
+                None if !partial.leading_fields.is_empty() => {
+                    break Some(Err(ParseError::new(
+                        partial.leading_fields[0].0,
+                        ParseErrorKind::TagOutsideEntry,
+                    )
+                    .into()))
+                }
+                None if partial.state == ParseState::InProgress && self.options.finalize_at_eof => {
+                    partial.finish(&self.options);
+                    let entry = partial.entry.take().unwrap();
+
+                    if self.options.skip_empty_entries && entry.is_empty() {
+                        break None;
+                    } else {
+                        break Some(Ok(entry));
+                    }
+                }
+                None if partial.state == ParseState::InProgress => {
+                    break Some(Err(ParseError::new(
+                        self.line_no,
+                        ParseErrorKind::UnterminatedEntry,
+                    )
+                    .into()))
+                }
+                None => break None,
+            }
+        };
+
+        self.done = true;
+        result
     }
+}
 
-    Ok(())
+fn ends_with_hyphenated_word(s: &str) -> bool {
+    let mut chars = s.chars().rev();
+    chars.next() == Some('-') && chars.next().is_some_and(char::is_alphabetic)
 }
 
-/// The type of a reference.
-///
-/// This type implements [Display](std::fmt::Display) and [FromStr](std::str::FromStr) to (de)serialize to/from strings.
-///
-/// # Abbreviations
-///
-/// This enum encodes standard abbreviations in its variants according to the table below.
-/// If the type of a reference doesn't match any of the below abbreviations, it is encoded in the `Other` variant.
+/// A visitor observing the structure of a RIS file as it is parsed by [parse_with_visitor](crate::parse_with_visitor).
 ///
-/// | Abbreviation | Variant                 |
-/// |--------------|-------------------------|
-/// | ABST         | `Abstract`              |
-/// | ADVS         | `AudiovisualMaterial`   |
-/// | AGGR         | `AggregatedDatabase`    |
-/// | ANCIENT      | `AncientText`           |
-/// | ART          | `ArtWork`               |
-/// | BILL         | `Bill`                  |
-/// | BLOG         | `Blog`                  |
-/// | BOOK         | `WholeBook`             |
-/// | CASE         | `Case`                  |
-/// | CHAP         | `BookChapter`           |
-/// | CHART        | `Chart`                 |
-/// | CLSWK        | `ClassicalWork`         |
-/// | COMP         | `ComputerProgram`       |
-/// | CONF         | `ConferenceProceeding`  |
-/// | CPAPER       | `ConferencePaper`       |
-/// | CTLG         | `Catalog`               |
-/// | DATA         | `DataFile`              |
-/// | DBASE        | `OnlineDatabase`        |
-/// | DICT         | `Dictionary`            |
-/// | EBOOK        | `ElectronicBook`        |
-/// | ECHAP        | `ElectronicBookSection` |
-/// | EDBOOK       | `EditedBook`            |
-/// | EJOUR        | `ElectronicArticle`     |
-/// | ELEC         | `WebPage`               |
-/// | ENCYC        | `Encyclopedia`          |
-/// | EQUA         | `Equation`              |
-/// | FIGURE       | `Figure`                |
-/// | GEN          | `Generic`               |
-/// | GOVDOC       | `GovernmentDocument`    |
-/// | GRANT        | `Grant`                 |
-/// | HEAR         | `Hearing`               |
-/// | ICOMM        | `InternetCommunication` |
-/// | INPR         | `InPress`               |
-/// | JFULL        | `JournalFull`           |
-/// | JOUR         | `Journal`               |
-/// | LEGAL        | `LegalRuleOrRegulation` |
-/// | MANSCPT      | `Manuscript`            |
-/// | MAP          | `Map`                   |
-/// | MGZN         | `MagazineArticle`       |
-/// | MPCT         | `MotionPicture`         |
-/// | MULTI        | `OnlineMultimedia`      |
-/// | MUSIC        | `MusicScore`            |
-/// | NEWS         | `Newspaper`             |
-/// | PAMP         | `Pamphlet`              |
-/// | PAT          | `Patent`                |
-/// | PCOMM        | `PersonalCommunication` |
-/// | RPRT         | `Report`                |
-/// | SER          | `SerialPublication`     |
-/// | SLIDE        | `Slide`                 |
-/// | SOUND        | `SoundRecording`        |
-/// | STAND        | `Standard`              |
-/// | STAT         | `Statute`               |
-/// | THES         | `ThesisOrDissertation`  |
-/// | UNPB         | `UnpublishedWork`       |
-/// | VIDEO        | `VideoRecording`        |
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
-pub enum ReferenceType {
-    Abstract,
-    AudiovisualMaterial,
-    AggregatedDatabase,
-    AncientText,
-    ArtWork,
-    Bill,
-    Blog,
-    WholeBook,
-    Case,
-    BookChapter,
-    Chart,
-    ClassicalWork,
-    ComputerProgram,
-    ConferenceProceeding,
-    ConferencePaper,
-    Catalog,
-    DataFile,
-    OnlineDatabase,
-    Dictionary,
-    ElectronicBook,
-    ElectronicBookSection,
-    EditedBook,
-    ElectronicArticle,
-    WebPage,
-    Encyclopedia,
-    Equation,
-    Figure,
-    Generic,
-    GovernmentDocument,
-    Grant,
-    Hearing,
-    InternetCommunication,
-    InPress,
-    JournalFull,
-    Journal,
-    LegalRuleOrRegulation,
-    Manuscript,
-    Map,
-    MagazineArticle,
-    MotionPicture,
-    OnlineMultimedia,
-    MusicScore,
-    Newspaper,
-    Pamphlet,
-    Patent,
-    PersonalCommunication,
-    Report,
-    SerialPublication,
-    Slide,
-    SoundRecording,
-    Standard,
-    Statute,
-    ThesisOrDissertation,
-    UnpublishedWork,
-    VideoRecording,
-    Other(String),
+/// All methods do nothing by default.
+pub trait RisVisitor {
+    /// Called on the `TY` tag starting an entry, with the raw reference type.
+    fn on_entry_start(&mut self, _reference_type: &str) {}
+    /// Called on each tag inside an entry, other than `TY` and `ER`.
+    fn on_field(&mut self, _tag: &str, _value: &str) {}
+    /// Called on the `ER` tag ending an entry.
+    fn on_entry_end(&mut self) {}
 }
 
-impl FromStr for ReferenceType {
-    type Err = Infallible;
+/// Parse a RIS file from a string, reporting its entries and tags to a visitor instead of building [Entries](crate::Entry).
+///
+/// Only the structure of the file is checked: each tag is reported as-is, without checking whether the tag is known,
+/// whether unique fields are repeated, or whether values such as dates are valid.
+pub fn parse_with_visitor<V: RisVisitor>(s: &str, visitor: &mut V) -> Result<(), ParseError> {
+    use ParseErrorKind::*;
 
-    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
-        use ReferenceType::*;
+    let mut in_entry = false;
+    let mut line_no = 0;
 
-        Ok(match s {
-            "ABST" => Abstract,
-            "ADVS" => AudiovisualMaterial,
-            "AGGR" => AggregatedDatabase,
-            "ANCIENT" => AncientText,
-            "ART" => ArtWork,
-            "BILL" => Bill,
-            "BLOG" => Blog,
-            "BOOK" => WholeBook,
-            "CASE" => Case,
-            "CHAP" => BookChapter,
-            "CHART" => Chart,
-            "CLSWK" => ClassicalWork,
-            "COMP" => ComputerProgram,
-            "CONF" => ConferenceProceeding,
-            "CPAPER" => ConferencePaper,
-            "CTLG" => Catalog,
-            "DATA" => DataFile,
-            "DBASE" => OnlineDatabase,
-            "DICT" => Dictionary,
-            "EBOOK" => ElectronicBook,
-            "ECHAP" => ElectronicBookSection,
-            "EDBOOK" => EditedBook,
-            "EJOUR" => ElectronicArticle,
-            "ELEC" => WebPage,
-            "ENCYC" => Encyclopedia,
-            "EQUA" => Equation,
-            "FIGURE" => Figure,
-            "GEN" => Generic,
-            "GOVDOC" => GovernmentDocument,
-            "GRANT" => Grant,
-            "HEAR" => Hearing,
-            "ICOMM" => InternetCommunication,
-            "INPR" => InPress,
-            "JFULL" => JournalFull,
-            "JOUR" => Journal,
-            "LEGAL" => LegalRuleOrRegulation,
-            "MANSCPT" => Manuscript,
-            "MAP" => Map,
-            "MGZN" => MagazineArticle,
-            "MPCT" => MotionPicture,
-            "MULTI" => OnlineMultimedia,
-            "MUSIC" => MusicScore,
-            "NEWS" => Newspaper,
-            "PAMP" => Pamphlet,
-            "PAT" => Patent,
-            "PCOMM" => PersonalCommunication,
-            "RPRT" => Report,
-            "SER" => SerialPublication,
-            "SLIDE" => Slide,
-            "SOUND" => SoundRecording,
-            "STAND" => Standard,
-            "STAT" => Statute,
-            "THES" => ThesisOrDissertation,
-            "UNPB" => UnpublishedWork,
-            "VIDEO" => VideoRecording,
-            _ => Other(s.to_owned()),
-        })
-    }
-}
+    for line in trim_bom(s).lines() {
+        line_no += 1;
 
-impl Display for ReferenceType {
-    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
-        use ReferenceType::*;
+        let line = trim_form_feeds(line);
 
-        let s = match self {
-            Abstract => "ABST",
-            AudiovisualMaterial => "ADVS",
-            AggregatedDatabase => "AGGR",
-            AncientText => "ANCIENT",
-            ArtWork => "ART",
-            Bill => "BILL",
-            Blog => "BLOG",
-            WholeBook => "BOOK",
-            Case => "CASE",
-            BookChapter => "CHAP",
-            Chart => "CHART",
-            ClassicalWork => "CLSWK",
-            ComputerProgram => "COMP",
-            ConferenceProceeding => "CONF",
-            ConferencePaper => "CPAPER",
-            Catalog => "CTLG",
-            DataFile => "DATA",
-            OnlineDatabase => "DBASE",
-            Dictionary => "DICT",
-            ElectronicBook => "EBOOK",
-            ElectronicBookSection => "ECHAP",
-            EditedBook => "EDBOOK",
-            ElectronicArticle => "EJOUR",
-            WebPage => "ELEC",
-            Encyclopedia => "ENCYC",
-            Equation => "EQUA",
-            Figure => "FIGURE",
-            Generic => "GEN",
-            GovernmentDocument => "GOVDOC",
-            Grant => "GRANT",
-            Hearing => "HEAR",
-            InternetCommunication => "ICOMM",
-            InPress => "INPR",
-            JournalFull => "JFULL",
-            Journal => "JOUR",
-            LegalRuleOrRegulation => "LEGAL",
-            Manuscript => "MANSCPT",
-            Map => "MAP",
-            MagazineArticle => "MGZN",
-            MotionPicture => "MPCT",
-            OnlineMultimedia => "MULTI",
-            MusicScore => "MUSIC",
-            Newspaper => "NEWS",
-            Pamphlet => "PAMP",
-            Patent => "PAT",
-            PersonalCommunication => "PCOMM",
-            Report => "RPRT",
-            SerialPublication => "SER",
-            Slide => "SLIDE",
-            SoundRecording => "SOUND",
-            Standard => "STAND",
-            Statute => "STAT",
-            ThesisOrDissertation => "THES",
-            UnpublishedWork => "UNPB",
-            VideoRecording => "VIDEO",
-            Other(s) => &s,
-        };
+        if line.is_empty() && !in_entry {
+            continue;
+        }
 
-        f.write_str(s)
+        let (key, value) = split_line(line).ok_or_else(|| ParseError::new(line_no, InvalidLine))?;
+
+        match (in_entry, key) {
+            (false, "TY") => {
+                in_entry = true;
+                visitor.on_entry_start(value);
+            }
+            (false, _) => return Err(ParseError::new(line_no, TagOutsideEntry)),
+            (true, "TY") => return Err(ParseError::new(line_no, UnterminatedEntry)),
+            (true, "ER") => {
+                if !value.is_empty() {
+                    return Err(ParseError::new(line_no, InvalidLine));
+                }
+
+                in_entry = false;
+                visitor.on_entry_end();
+            }
+            (true, _) => visitor.on_field(key, value),
+        }
+    }
+
+    if in_entry {
+        Err(ParseError::new(line_no, UnterminatedEntry))
+    } else {
+        Ok(())
     }
 }
 
-/// The (partial) date of publication of a reference.
+/// Check that a string has the structure of a RIS file, without building any [Entries](crate::Entry).
 ///
-/// The `year` field is mandatory, all the others are optional.
+/// This is a faster check than parsing, which only makes sure that every line is a well-formed tag,
+/// and that every entry starts with `TY` and ends with `ER`.
+/// Like [parse_with_visitor](crate::parse_with_visitor), it doesn't check tags or values.
+pub fn validate_structure(s: &str) -> Result<(), ParseError> {
+    struct Validator;
+    impl RisVisitor for Validator {}
+
+    parse_with_visitor(s, &mut Validator)
+}
+
+/// The reference types of all the entries of a RIS file, in order, without parsing any of their other fields.
 ///
-/// This type implements [Display](std::fmt::Display) and [FromStr](std::str::FromStr) to (de)serialize to/from strings.
+/// This is much faster than a full parse for listing the contents of a large file.
+/// The structure of the file is checked as in [validate_structure](crate::validate_structure).
+pub fn list_reference_types(s: &str) -> Result<Vec<ReferenceType>, ParseError> {
+    struct TypeLister(Vec<ReferenceType>);
+    impl RisVisitor for TypeLister {
+        fn on_entry_start(&mut self, reference_type: &str) {
+            self.0.push(reference_type.parse().unwrap());
+        }
+    }
+
+    let mut lister = TypeLister(Vec::new());
+    parse_with_visitor(s, &mut lister)?;
+    Ok(lister.0)
+}
+
+/// Rewrite a RIS file line by line, from a reader to a writer, without keeping more than a line in memory.
 ///
-/// Some examples of valid strings:
-/// - `1998///`
-/// - `1995/12/01/someotherinfo`
-/// - `1998/03//`
-/// - `1998///someotherinfo`
-/// - `2001`
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
-pub struct PublicationDate {
-    pub year: i32,
-    pub month: Option<i32>,
-    pub day: Option<i32>,
-    pub other_info: Option<String>,
+/// `f` is called with the tag and value of every tag line, including `TY` and `ER`, and returns the tag and value
+/// to write in its place, or `None` to drop the line, such as to strip all the `UR` tags of a large file.
+/// Other lines, such as blank lines between entries or continuation lines, are written as they are.
+/// Lines keep their original line endings, but a byte order mark at the start of the file is dropped.
+///
+/// No parsing or validation is done, so dropping `TY` or `ER` lines produces an invalid file.
+pub fn transform_stream<R: BufRead, W: io::Write>(
+    mut r: R,
+    mut w: W,
+    mut f: impl FnMut(&str, &str) -> Option<(String, String)>,
+) -> io::Result<()> {
+    let mut buf = String::new();
+    let mut first = true;
+
+    while r.read_line(&mut buf)? > 0 {
+        let line = if first { trim_bom(&buf) } else { &buf };
+        let content = line.trim_end_matches(&['\r', '\n'][..]);
+        let line_ending = &line[content.len()..];
+
+        match split_line(content) {
+            Some((tag, value)) => {
+                if let Some((tag, value)) = f(tag, value) {
+                    write!(w, "{}  - {}{}", tag, value, line_ending)?;
+                }
+            }
+            None => w.write_all(line.as_bytes())?,
+        }
+
+        buf.clear();
+        first = false;
+    }
+
+    Ok(())
 }
 
-impl PublicationDate {
-    pub fn new(
-        year: i32,
-        month: Option<i32>,
-        day: Option<i32>,
-        other_info: Option<String>,
-    ) -> Self {
-        Self {
-            year,
-            month,
-            day,
-            other_info,
+/// Split a RIS file into about `n` chunks of similar length, each ending after an `ER` line,
+/// with the number of lines preceding each chunk.
+#[cfg(feature = "std-threads")]
+fn split_entry_chunks(s: &str, n: usize) -> Vec<(usize, &str)> {
+    let target = s.len() / n.max(1) + 1;
+    let mut chunks = Vec::new();
+    let (mut start, mut start_line) = (0, 0);
+    let (mut position, mut line_no) = (0, 0);
+
+    for line in s.split_inclusive('\n') {
+        position += line.len();
+        line_no += 1;
+
+        let line = trim_form_feeds(line.trim_end_matches(&['\n', '\r'][..]));
+        let is_end = line == "ER  - " || line == "ER  -";
+
+        if is_end && position - start >= target {
+            chunks.push((start_line, &s[start..position]));
+            start = position;
+            start_line = line_no;
         }
     }
+
+    if start < s.len() {
+        chunks.push((start_line, &s[start..]));
+    }
+
+    chunks
 }
 
-/// An error occurring during the parsing of a publication date
-pub struct ParseDateError;
+/// All the tags recognized by the parser, including `TY` and `ER`.
+const KNOWN_TAGS: &[&str] = &[
+    "TY", "ER", "ID", "T1", "TI", "T2", "T3", "ST", "A1", "AU", "A2", "ED", "A3", "AF", "Y1", "PY",
+    "DA", "Y2", "N1", "AB", "N2", "KW", "CR", "RP", "AV", "CA", "CN", "CL", "DO", "AN", "UR", "LK",
+    "L1", "SP", "EP", "JF", "JO", "JA", "J1", "J2", "VL", "IS", "CY", "PB", "SN", "AD", "WP", "U1",
+    "U2", "U3", "U4", "U5", "C1", "C2", "C3", "C4", "C5", "C6", "C7", "C8", "M1", "M2", "M3", "TC",
+    "Z9", "BT",
+];
+
+/// Remove the byte order mark at the start of a file, which some Windows tools write at the start of UTF-8 files.
+fn trim_bom(s: &str) -> &str {
+    s.strip_prefix('\u{FEFF}').unwrap_or(s)
+}
 
-impl FromStr for PublicationDate {
-    type Err = ParseDateError;
+/// Remove the form feeds at the start and end of a line, which some systems use to separate concatenated RIS files.
+fn trim_form_feeds(line: &str) -> &str {
+    line.trim_matches('\x0C')
+}
 
-    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
-        lazy_static! {
-            static ref DATE_RE: Regex =
-                Regex::new("(\\d\\d\\d\\d)(?:/(\\d\\d)?(?:/(\\d\\d)?(?:/(.+)?)?)?)?").unwrap();
+/// Split a line into its key and value.
+fn split_line(line: &str) -> Option<(&str, &str)> {
+    // Many files terminate entries with `ER  -`, without the trailing space
+    if line == "ER  -" {
+        return Some(("ER", ""));
+    }
+
+    let matches = LINE_RE.captures(line)?;
+
+    Some((
+        matches.get(1).unwrap().as_str(),
+        matches.get(2).unwrap().as_str(),
+    ))
+}
+
+struct PartialEntry {
+    entry: Option<Entry>,
+    state: ParseState,
+    /// Fields found before `TY`, with their line numbers, if `allow_ty_anywhere` is set
+    leading_fields: Vec<(usize, String, String)>,
+    /// `N2` values, if `n2_as_notes` is set, which are appended to the notes once the entry is finished
+    n2_notes: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ParseState {
+    /// Before TY
+    Start,
+    /// After TY and before ER
+    InProgress,
+    /// After ER
+    End,
+}
+
+impl PartialEntry {
+    fn new() -> Self {
+        Self {
+            entry: None,
+            state: ParseState::Start,
+            leading_fields: Vec::new(),
+            n2_notes: Vec::new(),
         }
+    }
 
-        let matches = DATE_RE.captures(s).ok_or(ParseDateError)?;
+    fn parse_line(
+        &mut self,
+        line: &str,
+        line_no: usize,
+        options: &ParseOptions,
+    ) -> Result<ParseState, ParseError> {
+        use ParseErrorKind::*;
 
-        let year = matches
-            .get(1)
-            .unwrap()
-            .as_str()
-            .parse()
-            .map_err(|_| ParseDateError)?;
+        let line = trim_form_feeds(line);
 
-        let month = matches
-            .get(2)
-            .map(|m| m.as_str().parse())
-            .transpose()
-            .map_err(|_| ParseDateError)?;
+        if line.is_empty() && self.state == ParseState::Start {
+            return Ok(self.state);
+        }
 
-        let day = matches
-            .get(3)
-            .map(|d| d.as_str().parse())
-            .transpose()
-            .map_err(|_| ParseDateError)?;
+        let (key, value) = split_line(line).ok_or_else(|| ParseError::new(line_no, InvalidLine))?;
 
-        let other_info = matches.get(4).map(|s| s.as_str().to_owned());
+        match self.state {
+            ParseState::Start => {
+                if key == "TY" {
+                    self.entry = Some(Entry::new(value.parse().unwrap()));
+
+                    for (line_no, key, value) in std::mem::take(&mut self.leading_fields) {
+                        self.set_field(&key, &value, line_no, options)?;
+                    }
+
+                    self.state = ParseState::InProgress;
+                } else if options.allow_ty_anywhere && key != "ER" {
+                    self.leading_fields
+                        .push((line_no, String::from(key), String::from(value)));
+                } else {
+                    return Err(ParseError::new(line_no, TagOutsideEntry));
+                }
+            }
+            ParseState::InProgress => match key {
+                "TY" => return Err(ParseError::new(line_no, UnterminatedEntry)),
+
+                "ER" => {
+                    if value.is_empty() {
+                        self.finish(options);
+                        self.state = ParseState::End;
+                    } else {
+                        return Err(ParseError::new(line_no, InvalidLine));
+                    }
+                }
+
+                _ => self.set_field(key, value, line_no, options)?,
+            },
+            ParseState::End => return Err(ParseError::new(line_no, TagOutsideEntry)),
+        }
+
+        Ok(self.state)
+    }
+
+    /// Set the field of the entry corresponding to a key other than `TY` and `ER`, resolving tag aliases.
+    fn set_field(
+        &mut self,
+        key: &str,
+        value: &str,
+        line_no: usize,
+        options: &ParseOptions,
+    ) -> Result<(), ParseError> {
+        let entry = self.entry.as_mut().unwrap();
+
+        let tag = match options.tag_aliases.get(key) {
+            Some(tag) if !KNOWN_TAGS.contains(&key) => {
+                if tag == "TY" || tag == "ER" || !KNOWN_TAGS.contains(&tag.as_str()) {
+                    return Err(ParseError::new(
+                        line_no,
+                        ParseErrorKind::InvalidKey(String::from(key)),
+                    ));
+                }
+
+                tag.as_str()
+            }
+            _ => key,
+        };
+
+        if tag == "N2" && options.n2_as_notes {
+            // Kept aside until the entry is finished, so that a repeated `N1` is still detected after it
+            self.n2_notes.push(String::from(value));
+            Ok(())
+        } else {
+            entry.set_field(tag, value, line_no, options)
+        }
+    }
+
+    /// Apply the options which depend on the whole entry, once all its fields are parsed.
+    fn finish(&mut self, options: &ParseOptions) {
+        let entry = self.entry.as_mut().unwrap();
+
+        for value in self.n2_notes.drain(..) {
+            append_to_field(&mut entry.notes, &value);
+        }
+
+        if options.m3_doi_as_doi
+            && entry.doi.as_deref().map_or(true, str::is_empty)
+            && entry
+                .misc_3
+                .as_deref()
+                .is_some_and(|misc_3| is_valid_doi(normalize_doi(misc_3)))
+        {
+            entry.doi = entry.misc_3.take();
+        }
+    }
+}
+
+/// The place a field is stored in, for [Entry](crate::Entry) and [EntryRef](crate::EntryRef).
+enum Slot<'s, T, D> {
+    Text(&'s mut Option<T>),
+    List(&'s mut Vec<T>),
+    Date(&'s mut Option<D>),
+}
+
+/// Defines [Field] and the slot of each field in [Entry](crate::Entry) and [EntryRef](crate::EntryRef),
+/// so that both are parsed with the same tag table in [field_for].
+macro_rules! parsed_fields {
+    ($($variant:ident => $slot:ident($field:ident),)*) => {
+        /// A field that tags are parsed into.
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        enum Field {
+            $($variant,)*
+        }
+
+        impl Entry {
+            fn slot(&mut self, field: Field) -> Slot<'_, String, PublicationDate> {
+                match field {
+                    $(Field::$variant => Slot::$slot(&mut self.$field),)*
+                }
+            }
+        }
+
+        impl<'a> EntryRef<'a> {
+            fn slot(&mut self, field: Field) -> Slot<'_, &'a str, &'a str> {
+                match field {
+                    $(Field::$variant => Slot::$slot(&mut self.$field),)*
+                }
+            }
+        }
+    };
+}
+
+parsed_fields! {
+    Id => Text(id),
+    Title => Text(title),
+    SecondaryTitle => Text(secondary_title),
+    TertiaryTitle => Text(tertiary_title),
+    ShortTitle => Text(short_title),
+    Authors => List(authors),
+    SecondaryAuthors => List(secondary_authors),
+    Editors => List(editors),
+    TertiaryAuthors => List(tertiary_authors),
+    FullAuthors => List(full_authors),
+    PrimaryDate => Date(primary_date),
+    SecondaryDate => Date(secondary_date),
+    Notes => Text(notes),
+    Abstract => Text(abstract_),
+    Keywords => List(keywords),
+    CitedReferences => List(cited_references),
+    Reprint => Text(reprint),
+    Availability => Text(availability),
+    Caption => Text(caption),
+    CallNumber => Text(call_number),
+    Classification => Text(classification),
+    Doi => Text(doi),
+    AccessionNumber => Text(accession_number),
+    Urls => List(urls),
+    FileAttachments => List(file_attachments),
+    StartPage => Text(start_page),
+    EndPage => Text(end_page),
+    Journal => Text(journal),
+    JournalAbbrev => Text(journal_abbrev),
+    JournalAbbrev1 => Text(journal_abbrev_1),
+    JournalAbbrev2 => Text(journal_abbrev_2),
+    Volume => Text(volume),
+    Issue => Text(issue),
+    City => Text(city),
+    Publisher => Text(publisher),
+    SerialNumber => Text(serial_number),
+    Address => Text(address),
+    Workplace => Text(workplace),
+    User1 => Text(user_1),
+    User2 => Text(user_2),
+    User3 => Text(user_3),
+    User4 => Text(user_4),
+    User5 => Text(user_5),
+    Custom1 => Text(custom_1),
+    Custom2 => Text(custom_2),
+    Custom3 => Text(custom_3),
+    Custom4 => Text(custom_4),
+    Custom5 => Text(custom_5),
+    Custom6 => Text(custom_6),
+    Custom7 => Text(custom_7),
+    Custom8 => Text(custom_8),
+    Misc1 => Text(misc_1),
+    Misc2 => Text(misc_2),
+    Misc3 => Text(misc_3),
+    WosTimesCited => Text(wos_times_cited),
+    TotalTimesCited => Text(total_times_cited),
+}
+
+/// The field a key other than `TY` and `ER` is parsed into, or `None` if the key isn't recognized.
+///
+/// `BT` depends on the reference type, see [Entry](crate::Entry).
+fn field_for(key: &str, reference_type: &ReferenceType) -> Option<Field> {
+    use Field::*;
+    use ReferenceType::{UnpublishedWork, WholeBook};
+
+    Some(match key {
+        "ID" => Id,
+
+        "T1" | "TI" => Title,
+        "T2" => SecondaryTitle,
+        "T3" => TertiaryTitle,
+        "ST" => ShortTitle,
+
+        "A1" | "AU" => Authors,
+        "A2" => SecondaryAuthors,
+        "ED" => Editors,
+        "A3" => TertiaryAuthors,
+        "AF" => FullAuthors,
+
+        "Y1" | "PY" | "DA" => PrimaryDate,
+        "Y2" => SecondaryDate,
+
+        "N1" => Notes,
+
+        "AB" | "N2" => Abstract,
+        "KW" => Keywords,
+        "CR" => CitedReferences,
+        "RP" => Reprint,
+        "AV" => Availability,
+
+        "CA" => Caption,
+        "CN" => CallNumber,
+        "CL" => Classification,
+        "DO" => Doi,
+        "AN" => AccessionNumber,
+        "UR" | "LK" => Urls,
+        "L1" => FileAttachments,
+
+        "SP" => StartPage,
+        "EP" => EndPage,
+
+        "JF" | "JO" => Journal,
+        "JA" => JournalAbbrev,
+        "J1" => JournalAbbrev1,
+        "J2" => JournalAbbrev2,
+
+        "VL" => Volume,
+        "IS" => Issue,
+        "CY" => City,
+        "PB" => Publisher,
+        "SN" => SerialNumber,
+        "AD" => Address,
+        "WP" => Workplace,
+
+        "U1" => User1,
+        "U2" => User2,
+        "U3" => User3,
+        "U4" => User4,
+        "U5" => User5,
+
+        "C1" => Custom1,
+        "C2" => Custom2,
+        "C3" => Custom3,
+        "C4" => Custom4,
+        "C5" => Custom5,
+        "C6" => Custom6,
+        "C7" => Custom7,
+        "C8" => Custom8,
+
+        "M1" => Misc1,
+        "M2" => Misc2,
+        "M3" => Misc3,
+        "TC" => WosTimesCited,
+        "Z9" => TotalTimesCited,
+
+        "BT" => match reference_type {
+            WholeBook | UnpublishedWork => Title,
+            _ => SecondaryTitle,
+        },
+
+        _ => return None,
+    })
+}
+
+impl Entry {
+    /// Set the field corresponding to a key other than `TY` and `ER`.
+    fn set_field(
+        &mut self,
+        key: &str,
+        value: &str,
+        line_no: usize,
+        options: &ParseOptions,
+    ) -> Result<(), ParseError> {
+        let field = match field_for(key, &self.reference_type) {
+            Some(field) => field,
+            None if options.preserve_unknown_tags => {
+                self.unknown_tags
+                    .push((String::from(key), String::from(value)));
+                return Ok(());
+            }
+            None => {
+                return Err(ParseError::new(
+                    line_no,
+                    ParseErrorKind::InvalidKey(String::from(key)),
+                ))
+            }
+        };
+
+        match self.slot(field) {
+            Slot::List(_) if options.skip_empty_repeated && value.trim().is_empty() => {}
+            Slot::List(authors)
+                if field == Field::Authors && options.split_authors_on_semicolon =>
+            {
+                authors.extend(
+                    value
+                        .split(';')
+                        .map(str::trim)
+                        .filter(|author| !author.is_empty())
+                        .map(String::from),
+                )
+            }
+            Slot::List(values) => values.push(String::from(value)),
+            Slot::Text(text) => set_text_field(text, value, line_no, options)?,
+            Slot::Date(date) if field == Field::PrimaryDate => {
+                set_primary_date(date, value, line_no, options.primary_date_conflicts)?
+            }
+            Slot::Date(date) => set_unique_field(date, value, line_no)?,
+        }
+
+        Ok(())
+    }
+}
+
+/// Set a text field which can only appear once, or append to it if
+/// [concat_repeated_unique_tags](crate::ParseOptions::concat_repeated_unique_tags) is set.
+#[inline(always)]
+fn set_text_field(
+    field: &mut Option<String>,
+    value: &str,
+    line_no: usize,
+    options: &ParseOptions,
+) -> Result<(), ParseError> {
+    match field {
+        Some(existing) if options.concat_repeated_unique_tags => {
+            existing.push(' ');
+            existing.push_str(value);
+            Ok(())
+        }
+        _ => set_unique_field(field, value, line_no),
+    }
+}
+
+#[inline(always)]
+fn set_unique_field<T>(field: &mut Option<T>, value: &str, line_no: usize) -> Result<(), ParseError>
+where
+    T: FromStr,
+    ParseErrorKind: From<T::Err>,
+{
+    if field.is_some() {
+        Err(ParseError::new(line_no, ParseErrorKind::DuplicateField))
+    } else {
+        *field = Some(
+            value
+                .parse()
+                .map_err(|e: T::Err| ParseError::new(line_no, e.into()))?,
+        );
+        Ok(())
+    }
+}
+
+/// Append a value to a field, on a new line if the field is already set.
+fn append_to_field(field: &mut Option<String>, value: &str) {
+    match field {
+        Some(existing) => {
+            existing.push('\n');
+            existing.push_str(value);
+        }
+        None => *field = Some(String::from(value)),
+    }
+}
+
+fn set_primary_date(
+    field: &mut Option<PublicationDate>,
+    value: &str,
+    line_no: usize,
+    policy: DateConflictPolicy,
+) -> Result<(), ParseError> {
+    let existing = match field {
+        Some(existing) => existing,
+        None => return set_unique_field(field, value, line_no),
+    };
+
+    let date: PublicationDate = value
+        .parse()
+        .map_err(|e: ParseDateError| ParseError::new(line_no, e.into()))?;
+
+    match policy {
+        DateConflictPolicy::Error => Err(ParseError::new(line_no, ParseErrorKind::DuplicateField)),
+        DateConflictPolicy::KeepFirst => Ok(()),
+        DateConflictPolicy::Merge => {
+            fn compatible<T: PartialEq>(a: &Option<T>, b: &Option<T>) -> bool {
+                a.is_none() || b.is_none() || a == b
+            }
+
+            if existing.year == date.year
+                && compatible(&existing.month, &date.month)
+                && compatible(&existing.day, &date.day)
+                && compatible(&existing.other_info, &date.other_info)
+            {
+                existing.month = existing.month.or(date.month);
+                existing.day = existing.day.or(date.day);
+                existing.other_info = existing.other_info.take().or(date.other_info);
+                Ok(())
+            } else {
+                Err(ParseError::new(line_no, ParseErrorKind::DuplicateField))
+            }
+        }
+    }
+}
+
+// FIXME Once `!` is stabilized, this conversion should be unnecessary
+#[doc(hidden)]
+impl From<std::convert::Infallible> for ParseErrorKind {
+    fn from(_: std::convert::Infallible) -> Self {
+        panic!();
+    }
+}
+
+#[doc(hidden)]
+impl From<ParseDateError> for ParseErrorKind {
+    fn from(_: ParseDateError) -> Self {
+        ParseErrorKind::InvalidDate
+    }
+}
+
+/// A borrowed view of an [Entry](crate::Entry), referencing the input it was parsed from instead of allocating its values.
+///
+/// The fields are the same as [Entry](crate::Entry), except for dates, which are kept as unparsed strings.
+/// The dates are still validated during parsing, so [EntryRef::to_owned](crate::EntryRef::to_owned) can't fail.
+///
+/// See [parse_borrowed](crate::parse_borrowed).
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct EntryRef<'a> {
+    pub reference_type: ReferenceType,
+
+    pub id: Option<&'a str>,
+    pub title: Option<&'a str>,
+    pub secondary_title: Option<&'a str>,
+    pub tertiary_title: Option<&'a str>,
+    pub short_title: Option<&'a str>,
+    pub authors: Vec<&'a str>,
+    pub secondary_authors: Vec<&'a str>,
+    pub editors: Vec<&'a str>,
+    pub tertiary_authors: Vec<&'a str>,
+    pub full_authors: Vec<&'a str>,
+    pub primary_date: Option<&'a str>,
+    pub secondary_date: Option<&'a str>,
+    pub notes: Option<&'a str>,
+    pub abstract_: Option<&'a str>,
+    pub keywords: Vec<&'a str>,
+    pub cited_references: Vec<&'a str>,
+    pub reprint: Option<&'a str>,
+    pub availability: Option<&'a str>,
+    pub caption: Option<&'a str>,
+    pub call_number: Option<&'a str>,
+    pub classification: Option<&'a str>,
+    pub doi: Option<&'a str>,
+    pub accession_number: Option<&'a str>,
+    pub urls: Vec<&'a str>,
+    pub file_attachments: Vec<&'a str>,
+    pub start_page: Option<&'a str>,
+    pub end_page: Option<&'a str>,
+    pub journal: Option<&'a str>,
+    pub journal_abbrev: Option<&'a str>,
+    pub journal_abbrev_1: Option<&'a str>,
+    pub journal_abbrev_2: Option<&'a str>,
+    pub volume: Option<&'a str>,
+    pub issue: Option<&'a str>,
+    pub city: Option<&'a str>,
+    pub publisher: Option<&'a str>,
+    pub serial_number: Option<&'a str>,
+    pub address: Option<&'a str>,
+    pub workplace: Option<&'a str>,
+    pub user_1: Option<&'a str>,
+    pub user_2: Option<&'a str>,
+    pub user_3: Option<&'a str>,
+    pub user_4: Option<&'a str>,
+    pub user_5: Option<&'a str>,
+    pub custom_1: Option<&'a str>,
+    pub custom_2: Option<&'a str>,
+    pub custom_3: Option<&'a str>,
+    pub custom_4: Option<&'a str>,
+    pub custom_5: Option<&'a str>,
+    pub custom_6: Option<&'a str>,
+    pub custom_7: Option<&'a str>,
+    pub custom_8: Option<&'a str>,
+    pub misc_1: Option<&'a str>,
+    pub misc_2: Option<&'a str>,
+    pub misc_3: Option<&'a str>,
+    pub wos_times_cited: Option<&'a str>,
+    pub total_times_cited: Option<&'a str>,
+}
+
+impl<'a> EntryRef<'a> {
+    fn new(reference_type: ReferenceType) -> Self {
+        Self {
+            reference_type,
+
+            id: None,
+            title: None,
+            secondary_title: None,
+            tertiary_title: None,
+            short_title: None,
+            authors: Vec::new(),
+            secondary_authors: Vec::new(),
+            editors: Vec::new(),
+            tertiary_authors: Vec::new(),
+            full_authors: Vec::new(),
+            primary_date: None,
+            secondary_date: None,
+            notes: None,
+            abstract_: None,
+            keywords: Vec::new(),
+            cited_references: Vec::new(),
+            reprint: None,
+            availability: None,
+            caption: None,
+            call_number: None,
+            classification: None,
+            doi: None,
+            accession_number: None,
+            urls: Vec::new(),
+            file_attachments: Vec::new(),
+            start_page: None,
+            end_page: None,
+            journal: None,
+            journal_abbrev: None,
+            journal_abbrev_1: None,
+            journal_abbrev_2: None,
+            volume: None,
+            issue: None,
+            city: None,
+            publisher: None,
+            serial_number: None,
+            address: None,
+            workplace: None,
+            user_1: None,
+            user_2: None,
+            user_3: None,
+            user_4: None,
+            user_5: None,
+            custom_1: None,
+            custom_2: None,
+            custom_3: None,
+            custom_4: None,
+            custom_5: None,
+            custom_6: None,
+            custom_7: None,
+            custom_8: None,
+            misc_1: None,
+            misc_2: None,
+            misc_3: None,
+            wos_times_cited: None,
+            total_times_cited: None,
+        }
+    }
+
+    /// Copy the borrowed values into an owned [Entry](crate::Entry).
+    pub fn to_owned(&self) -> Entry {
+        Entry {
+            reference_type: self.reference_type.clone(),
+
+            id: self.id.map(String::from),
+            title: self.title.map(String::from),
+            secondary_title: self.secondary_title.map(String::from),
+            tertiary_title: self.tertiary_title.map(String::from),
+            short_title: self.short_title.map(String::from),
+            authors: self.authors.iter().map(|v| String::from(*v)).collect(),
+            secondary_authors: self
+                .secondary_authors
+                .iter()
+                .map(|v| String::from(*v))
+                .collect(),
+            editors: self.editors.iter().map(|v| String::from(*v)).collect(),
+            tertiary_authors: self
+                .tertiary_authors
+                .iter()
+                .map(|v| String::from(*v))
+                .collect(),
+            full_authors: self.full_authors.iter().map(|v| String::from(*v)).collect(),
+            primary_date: self.primary_date.and_then(|d| d.parse().ok()),
+            secondary_date: self.secondary_date.and_then(|d| d.parse().ok()),
+            notes: self.notes.map(String::from),
+            abstract_: self.abstract_.map(String::from),
+            keywords: self.keywords.iter().map(|v| String::from(*v)).collect(),
+            cited_references: self
+                .cited_references
+                .iter()
+                .map(|v| String::from(*v))
+                .collect(),
+            reprint: self.reprint.map(String::from),
+            availability: self.availability.map(String::from),
+            caption: self.caption.map(String::from),
+            call_number: self.call_number.map(String::from),
+            classification: self.classification.map(String::from),
+            doi: self.doi.map(String::from),
+            accession_number: self.accession_number.map(String::from),
+            urls: self.urls.iter().map(|v| String::from(*v)).collect(),
+            file_attachments: self
+                .file_attachments
+                .iter()
+                .map(|v| String::from(*v))
+                .collect(),
+            start_page: self.start_page.map(String::from),
+            end_page: self.end_page.map(String::from),
+            journal: self.journal.map(String::from),
+            journal_abbrev: self.journal_abbrev.map(String::from),
+            journal_abbrev_1: self.journal_abbrev_1.map(String::from),
+            journal_abbrev_2: self.journal_abbrev_2.map(String::from),
+            volume: self.volume.map(String::from),
+            issue: self.issue.map(String::from),
+            city: self.city.map(String::from),
+            publisher: self.publisher.map(String::from),
+            serial_number: self.serial_number.map(String::from),
+            address: self.address.map(String::from),
+            workplace: self.workplace.map(String::from),
+            user_1: self.user_1.map(String::from),
+            user_2: self.user_2.map(String::from),
+            user_3: self.user_3.map(String::from),
+            user_4: self.user_4.map(String::from),
+            user_5: self.user_5.map(String::from),
+            custom_1: self.custom_1.map(String::from),
+            custom_2: self.custom_2.map(String::from),
+            custom_3: self.custom_3.map(String::from),
+            custom_4: self.custom_4.map(String::from),
+            custom_5: self.custom_5.map(String::from),
+            custom_6: self.custom_6.map(String::from),
+            custom_7: self.custom_7.map(String::from),
+            custom_8: self.custom_8.map(String::from),
+            misc_1: self.misc_1.map(String::from),
+            misc_2: self.misc_2.map(String::from),
+            misc_3: self.misc_3.map(String::from),
+
+            wos_times_cited: self.wos_times_cited.map(String::from),
+            total_times_cited: self.total_times_cited.map(String::from),
+            unknown_tags: Vec::new(),
+        }
+    }
+
+    fn set_field(&mut self, key: &str, value: &'a str, line_no: usize) -> Result<(), ParseError> {
+        let field = field_for(key, &self.reference_type).ok_or_else(|| {
+            ParseError::new(line_no, ParseErrorKind::InvalidKey(String::from(key)))
+        })?;
+
+        match self.slot(field) {
+            Slot::Text(text) => set_unique_ref(text, value, line_no),
+            Slot::List(values) => {
+                values.push(value);
+                Ok(())
+            }
+            Slot::Date(date) => set_unique_date_ref(date, value, line_no),
+        }
+    }
+}
+
+/// Parse a RIS file from a string into [EntryRefs](crate::EntryRef) borrowing from it, avoiding allocating the values.
+///
+/// This always parses strictly, since the lenient [ParseOptions](crate::ParseOptions) may need to modify the values.
+pub fn parse_borrowed(s: &str) -> Result<Vec<EntryRef<'_>>, ParseError> {
+    use ParseErrorKind::*;
+
+    let mut entries = Vec::new();
+    let mut current: Option<EntryRef> = None;
+    let mut line_no = 0;
+
+    for line in trim_bom(s).lines() {
+        line_no += 1;
+
+        let line = trim_form_feeds(line);
+
+        if line.is_empty() && current.is_none() {
+            continue;
+        }
+
+        let (key, value) = split_line(line).ok_or_else(|| ParseError::new(line_no, InvalidLine))?;
+
+        match (current.as_mut(), key) {
+            (None, "TY") => current = Some(EntryRef::new(value.parse().unwrap())),
+            (None, _) => return Err(ParseError::new(line_no, TagOutsideEntry)),
+            (Some(_), "TY") => return Err(ParseError::new(line_no, UnterminatedEntry)),
+            (Some(_), "ER") => {
+                if !value.is_empty() {
+                    return Err(ParseError::new(line_no, InvalidLine));
+                }
+
+                entries.extend(current.take());
+            }
+            (Some(entry), _) => entry.set_field(key, value, line_no)?,
+        }
+    }
+
+    if current.is_some() {
+        Err(ParseError::new(line_no, UnterminatedEntry))
+    } else {
+        Ok(entries)
+    }
+}
+
+#[inline(always)]
+fn set_unique_ref<'a>(
+    field: &mut Option<&'a str>,
+    value: &'a str,
+    line_no: usize,
+) -> Result<(), ParseError> {
+    if field.is_some() {
+        Err(ParseError::new(line_no, ParseErrorKind::DuplicateField))
+    } else {
+        *field = Some(value);
+        Ok(())
+    }
+}
+
+fn set_unique_date_ref<'a>(
+    field: &mut Option<&'a str>,
+    value: &'a str,
+    line_no: usize,
+) -> Result<(), ParseError> {
+    value
+        .parse::<PublicationDate>()
+        .map_err(|e| ParseError::new(line_no, e.into()))?;
+
+    set_unique_ref(field, value, line_no)
+}
+
+impl Display for RIS {
+    /// Serializes a slice of entries into a multi-entry RIS string
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write_entries(f, self.0.iter(), &SerializeOptions::default())
+    }
+}
+
+/// Serializes entries into a multi-entry RIS string, separated by line endings.
+fn write_entries<'a, W: fmt::Write>(
+    w: &mut W,
+    entries: impl IntoIterator<Item = &'a Entry>,
+    options: &SerializeOptions,
+) -> fmt::Result {
+    let mut empty = true;
+
+    for entry in entries {
+        if !empty {
+            write!(w, "{}", options.line_ending)?;
+        }
+
+        entry.write_ris(w, options)?;
+        empty = false;
+    }
+
+    if options.trailing_newline && !empty {
+        write!(w, "{}", options.line_ending)?;
+    }
+
+    Ok(())
+}
+
+/// A single entry in the RIS file, started by a `TY` and terminated by a `ER`.
+///
+/// This type implements [Display](std::fmt::Display) and [FromStr](std::str::FromStr) to (de)serialize to/from strings.
+/// To (de)serialize files composed of multiple records, see [RIS](crate::RIS)
+///
+/// # Field mappings
+///
+/// All fields except `TY` are optional, and are stored as `Option`s.
+/// Repeated fields are invalid, and will cause a [ParseError](crate::ParseError).
+///
+/// [ReferenceType]: crate::ReferenceType
+/// [String]: std::string::String
+/// [PublicationDate]: crate::PublicationDate
+///
+/// | Key  | Field               | Type              |
+/// |------|---------------------|-------------------|
+/// | `TY` | `reference_type`    | [ReferenceType]   |
+/// | `ID` | `id`                | [String]          |
+/// | `T1` | `title`             | [String]          |
+/// | `T2` | `secondary_title`   | [String]          |
+/// | `T3` | `tertiary_title`    | [String]          |
+/// | `ST` | `short_title`       | [String]          |
+/// | `Y1` | `primary_date`      | [PublicationDate] |
+/// | `Y2` | `secondary_date`    | [PublicationDate] |
+/// | `N1` | `notes`             | [String]          |
+/// | `N2` | `abstract_`         | [String]          |
+/// | `RP` | `reprint`           | [String]          |
+/// | `AV` | `availability`      | [String]          |
+/// | `CA` | `caption`           | [String]          |
+/// | `CN` | `call_number`       | [String]          |
+/// | `CL` | `classification`    | [String]          |
+/// | `DO` | `doi`               | [String]          |
+/// | `AN` | `accession_number`  | [String]          |
+/// | `SP` | `start_page`        | [String]          |
+/// | `EP` | `end_page`          | [String]          |
+/// | `JF` | `journal`           | [String]          |
+/// | `JA` | `journal_abbrev`    | [String]          |
+/// | `J1` | `journal_abbrev_1`  | [String]          |
+/// | `J2` | `journal_abbrev_2`  | [String]          |
+/// | `VL` | `volume`            | [String]          |
+/// | `IS` | `issue`             | [String]          |
+/// | `CY` | `city`              | [String]          |
+/// | `PB` | `publisher`         | [String]          |
+/// | `SN` | `serial_number`     | [String]          |
+/// | `AD` | `address`           | [String]          |
+/// | `WP` | `workplace`         | [String]          |
+/// | `U1` | `user_1`            | [String]          |
+/// | `U2` | `user_2`            | [String]          |
+/// | `U3` | `user_3`            | [String]          |
+/// | `U4` | `user_4`            | [String]          |
+/// | `U5` | `user_5`            | [String]          |
+/// | `C1` | `custom_1`          | [String]          |
+/// | `C2` | `custom_2`          | [String]          |
+/// | `C3` | `custom_3`          | [String]          |
+/// | `C4` | `custom_4`          | [String]          |
+/// | `C5` | `custom_5`          | [String]          |
+/// | `C6` | `custom_6`          | [String]          |
+/// | `C7` | `custom_7`          | [String]          |
+/// | `C8` | `custom_8`          | [String]          |
+/// | `M1` | `misc_1`            | [String]          |
+/// | `M2` | `misc_2`            | [String]          |
+/// | `M3` | `misc_3`            | [String]          |
+/// | `TC` | `wos_times_cited`   | [String]          |
+/// | `Z9` | `total_times_cited` | [String]          |
+///
+/// Some fields are `Vec`s, and the corresponding keys are allowed to appear multiple times:
+///
+/// | Key  | Field              | Type     |
+/// |------|--------------------|----------|
+/// | `A1` | `authors`          | [String] |
+/// | `A2` | `second_authors`   | [String] |
+/// | `ED` | `editors`          | [String] |
+/// | `A3` | `tertiary_authors` | [String] |
+/// | `AF` | `full_authors`     | [String] |
+/// | `KW` | `keywords`         | [String] |
+/// | `CR` | `cited_references` | [String] |
+/// | `UR` | `urls`             | [String] |
+/// | `L1` | `file_attachments` | [String] |
+///
+/// Unrecognized keys are invalid and will cause a [ParseError](crate::ParseError), unless
+/// [preserve_unknown_tags](crate::ParseOptions::preserve_unknown_tags) is set, in which case they are stored in order
+/// in `unknown_tags` as pairs of key and value, and serialized back as they are.
+/// Keys are an uppercase letter followed by an uppercase letter or a digit, such as `A9` or `Z1`:
+/// lines which don't start with such a key are always invalid, even with `preserve_unknown_tags`.
+///
+/// # Field oddities
+///
+/// **Note that the following behaviours are inconsistently documented and I am by no means a bibliography expert.**
+/// **I wasn't able to find authoritative, conclusive documentation on how to handle them in a standard manner, so this is my best attempt at it.**
+/// **Any help hunting down information would be appreciated.**
+///
+/// During parsing, some keys are considered synonims and mapped to a common field:
+///
+/// | Key  | Synonims   | Field             |
+/// |------|------------|-------------------|
+/// | `T1` | `TI`       | `title`           |
+/// | `A1` | `AU`       | `first_authors`   |
+/// | `JF` | `JO`       | `journal`         |
+/// | `Y1` | `PY`, `DA` | `primary_date`    |
+/// | `N2` | `AB`       | `abstract_`       |
+/// | `UR` | `LK`       | `urls`            |
+///
+/// `ED` is not a synonym of `A2`, even though both are used for editors, so that editors parsed from `ED` are
+/// serialized back as `ED`. Use both `secondary_authors` and `editors` to get all the editors of an entry.
+///
+/// Some dialects use `N2` as a second notes field instead: see [n2_as_notes](crate::ParseOptions::n2_as_notes).
+/// Links from `LK`, which some tools use instead of `UR`, are collected into `urls` with the `UR` links
+/// in order of appearance, and serialized back as `UR`.
+///
+/// `AD` is always the author's or publisher's address, as in the RIS specification, even though a few dialects
+/// use it for an access date. It is unrelated to `AN`, the accession number in a database such as PubMed.
+///
+/// Some synonims are mapped conditionally depending on the reference type `TY`:
+///
+/// | `TY`               | Key  | Synonims | Field             |
+/// |--------------------|------|----------|-------------------|
+/// | `Whole Book`       | `T1` | `BT`     | `title`           |
+/// | `Unpublished Work` | `T2` | `BT`     | `secondary_title` |
+/// | `Unpublished Work` | `T1` | `CT`     | `title`           |
+///
+/// Some bibliography systems may resolve a journal abbreviation (`JA/J2`) as a standard abbreviated name for a journal, and automatically populate `T2` with the full journal name.
+/// This behaviour is not implemented as I could only find inconsistent documentation for it.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Entry {
+    pub reference_type: ReferenceType, // TY
+
+    pub id: Option<String>, // ID
+
+    pub title: Option<String>,           // T1, TI
+    pub secondary_title: Option<String>, // T2
+    pub tertiary_title: Option<String>,  // T3
+    pub short_title: Option<String>,     // ST
+
+    pub authors: Vec<String>,           // AU, A1
+    pub secondary_authors: Vec<String>, // A2
+    pub editors: Vec<String>,           // ED
+    pub tertiary_authors: Vec<String>,  // A3
+    pub full_authors: Vec<String>,      // AF
+
+    pub primary_date: Option<PublicationDate>, // PY, Y1, DA
+    pub secondary_date: Option<PublicationDate>, // Y2
+
+    pub notes: Option<String>, // N1
+
+    pub abstract_: Option<String>,        // AB, N2
+    pub keywords: Vec<String>,            // KW
+    pub cited_references: Vec<String>,    // CR
+    pub reprint: Option<String>,          // RP
+    pub availability: Option<String>,     // AV
+    pub caption: Option<String>,          // CA
+    pub call_number: Option<String>,      // CN
+    pub classification: Option<String>,   // CL
+    pub doi: Option<String>,              // DO
+    pub accession_number: Option<String>, // AN
+    pub urls: Vec<String>,                // UR, LK
+    pub file_attachments: Vec<String>,    // L1
+
+    pub start_page: Option<String>, // SP
+    pub end_page: Option<String>,   // EP
+
+    pub journal: Option<String>,          // JF, JO
+    pub journal_abbrev: Option<String>,   // JA
+    pub journal_abbrev_1: Option<String>, // J1
+    pub journal_abbrev_2: Option<String>, // J2
+
+    pub volume: Option<String>,        // VL
+    pub issue: Option<String>,         // IS
+    pub city: Option<String>,          // CY
+    pub publisher: Option<String>,     // PB
+    pub serial_number: Option<String>, // SN
+    pub address: Option<String>,       // AD
+    pub workplace: Option<String>,     // WP
+
+    pub user_1: Option<String>, // U1
+    pub user_2: Option<String>, // U2
+    pub user_3: Option<String>, // U3
+    pub user_4: Option<String>, // U4
+    pub user_5: Option<String>, // U5
+
+    pub custom_1: Option<String>, // C1
+    pub custom_2: Option<String>, // C2
+    pub custom_3: Option<String>, // C3
+    pub custom_4: Option<String>, // C4
+    pub custom_5: Option<String>, // C5
+    pub custom_6: Option<String>, // C6
+    pub custom_7: Option<String>, // C7
+    pub custom_8: Option<String>, // C8
+
+    pub misc_1: Option<String>, // M1
+    pub misc_2: Option<String>, // M2
+    pub misc_3: Option<String>, // M3
+
+    pub wos_times_cited: Option<String>,   // TC
+    pub total_times_cited: Option<String>, // Z9
+
+    pub unknown_tags: Vec<(String, String)>,
+}
+
+impl Entry {
+    pub fn new(reference_type: ReferenceType) -> Self {
+        Self {
+            reference_type,
+
+            id: None,
+
+            title: None,
+            secondary_title: None,
+            tertiary_title: None,
+            short_title: None,
+
+            authors: Vec::new(),
+            secondary_authors: Vec::new(),
+            editors: Vec::new(),
+            tertiary_authors: Vec::new(),
+            full_authors: Vec::new(),
+
+            primary_date: None,
+            secondary_date: None,
+
+            notes: None,
+
+            abstract_: None,
+            keywords: Vec::new(),
+            cited_references: Vec::new(),
+            reprint: None,
+            availability: None,
+            caption: None,
+            call_number: None,
+            classification: None,
+            doi: None,
+            accession_number: None,
+            urls: Vec::new(),
+            file_attachments: Vec::new(),
+
+            start_page: None,
+            end_page: None,
+
+            journal_abbrev: None,
+            journal: None,
+            journal_abbrev_1: None,
+            journal_abbrev_2: None,
+
+            volume: None,
+            issue: None,
+            city: None,
+            publisher: None,
+            serial_number: None,
+            address: None,
+            workplace: None,
+
+            user_1: None,
+            user_2: None,
+            user_3: None,
+            user_4: None,
+            user_5: None,
+
+            custom_1: None,
+            custom_2: None,
+            custom_3: None,
+            custom_4: None,
+            custom_5: None,
+            custom_6: None,
+            custom_7: None,
+            custom_8: None,
+
+            misc_1: None,
+            misc_2: None,
+            misc_3: None,
+
+            wos_times_cited: None,
+            total_times_cited: None,
+
+            unknown_tags: Vec::new(),
+        }
+    }
+
+    /// Change the reference type, keeping all other fields.
+    ///
+    /// Note that fields which are mapped conditionally on the reference type during parsing (such as `BT`)
+    /// are not reinterpreted.
+    pub fn with_reference_type(mut self, reference_type: ReferenceType) -> Self {
+        self.reference_type = reference_type;
+        self
+    }
+
+    /// The number of times this reference has been cited, according to `user_2`.
+    ///
+    /// This relies on the convention, used by some databases, of storing a citation count in `U2`.
+    /// Returns `None` if `user_2` is absent or isn't a number.
+    ///
+    /// Web of Science exports store their citation counts in `TC` and `Z9` instead,
+    /// which are parsed into `wos_times_cited` and `total_times_cited`.
+    pub fn times_cited(&self) -> Option<u32> {
+        self.user_2.as_ref()?.trim().parse().ok()
+    }
+
+    /// Parse the raw strings in `authors` into [Authors](crate::Author).
+    pub fn parsed_authors(&self) -> Vec<Author> {
+        self.authors.iter().map(|a| a.parse().unwrap()).collect()
+    }
+
+    /// The DOI with any `doi:` or `https://doi.org/` style prefix removed.
+    pub fn normalized_doi(&self) -> Option<&str> {
+        self.doi.as_deref().map(normalize_doi)
+    }
+
+    /// Whether the DOI matches the basic DOI syntax of `10.<registrant>/<suffix>`, or `None` if there is no DOI.
+    ///
+    /// The DOI is normalized first (see [normalized_doi](crate::Entry::normalized_doi)), so URL-prefixed DOIs are also valid.
+    pub fn doi_is_valid(&self) -> Option<bool> {
+        self.normalized_doi().map(is_valid_doi)
+    }
+
+    /// Apply a single raw RIS line to this entry, as if it appeared inside it in a RIS file.
+    ///
+    /// This allows building or patching an entry incrementally. `TY` sets the reference type, while `ER` is a no-op.
+    /// Errors are reported on line 1.
+    pub fn apply_line(&mut self, line: &str) -> Result<(), ParseError> {
+        let (key, value) =
+            split_line(line).ok_or_else(|| ParseError::new(1, ParseErrorKind::InvalidLine))?;
+
+        match key {
+            "TY" => self.reference_type = value.parse().unwrap(),
+            "ER" => {}
+            _ => self.set_field(key, value, 1, &ParseOptions::default())?,
+        }
+
+        Ok(())
+    }
+
+    /// A normalized version of the title, for fuzzy matching and deduplication.
+    ///
+    /// The title is normalized by:
+    /// 1. Lowercasing it
+    /// 2. Removing all characters which are neither alphanumeric nor whitespace
+    /// 3. Collapsing runs of whitespace into a single space, and trimming it from both ends
+    pub fn normalized_title(&self) -> Option<String> {
+        self.title.as_deref().map(normalize_text)
+    }
+
+    /// The names of the fields [recommended](crate::ReferenceType::recommended_fields) for the type of this entry which are missing.
+    ///
+    /// Since many exporters put the journal name in `T2`, `journal` is considered present if `secondary_title` is set.
+    pub fn missing_recommended_fields(&self) -> Vec<&'static str> {
+        self.reference_type
+            .recommended_fields()
+            .iter()
+            .copied()
+            .filter(|&name| match name {
+                "journal" => self.journal.is_none() && self.secondary_title.is_none(),
+                _ => !self.is_field_set(name),
+            })
+            .collect()
+    }
+
+    /// Whether the field with the given name is set, i.e. is `Some` or a non-empty `Vec`.
+    fn is_field_set(&self, name: &str) -> bool {
+        self.fields()
+            .iter()
+            .any(|(n, value)| *n == name && value.is_set())
+    }
+
+    /// All the fields other than `reference_type`, paired with their names, in declaration order.
+    fn fields(&self) -> Vec<(&'static str, FieldValue<'_>)> {
+        use FieldValue::*;
+
+        vec![
+            ("id", Text(self.id.as_deref())),
+            ("title", Text(self.title.as_deref())),
+            ("secondary_title", Text(self.secondary_title.as_deref())),
+            ("tertiary_title", Text(self.tertiary_title.as_deref())),
+            ("short_title", Text(self.short_title.as_deref())),
+            ("authors", List(&self.authors)),
+            ("secondary_authors", List(&self.secondary_authors)),
+            ("editors", List(&self.editors)),
+            ("tertiary_authors", List(&self.tertiary_authors)),
+            ("full_authors", List(&self.full_authors)),
+            ("primary_date", Date(self.primary_date.as_ref())),
+            ("secondary_date", Date(self.secondary_date.as_ref())),
+            ("notes", Text(self.notes.as_deref())),
+            ("abstract_", Text(self.abstract_.as_deref())),
+            ("keywords", List(&self.keywords)),
+            ("cited_references", List(&self.cited_references)),
+            ("reprint", Text(self.reprint.as_deref())),
+            ("availability", Text(self.availability.as_deref())),
+            ("caption", Text(self.caption.as_deref())),
+            ("call_number", Text(self.call_number.as_deref())),
+            ("classification", Text(self.classification.as_deref())),
+            ("doi", Text(self.doi.as_deref())),
+            ("accession_number", Text(self.accession_number.as_deref())),
+            ("urls", List(&self.urls)),
+            ("file_attachments", List(&self.file_attachments)),
+            ("start_page", Text(self.start_page.as_deref())),
+            ("end_page", Text(self.end_page.as_deref())),
+            ("journal", Text(self.journal.as_deref())),
+            ("journal_abbrev", Text(self.journal_abbrev.as_deref())),
+            ("journal_abbrev_1", Text(self.journal_abbrev_1.as_deref())),
+            ("journal_abbrev_2", Text(self.journal_abbrev_2.as_deref())),
+            ("volume", Text(self.volume.as_deref())),
+            ("issue", Text(self.issue.as_deref())),
+            ("city", Text(self.city.as_deref())),
+            ("publisher", Text(self.publisher.as_deref())),
+            ("serial_number", Text(self.serial_number.as_deref())),
+            ("address", Text(self.address.as_deref())),
+            ("workplace", Text(self.workplace.as_deref())),
+            ("user_1", Text(self.user_1.as_deref())),
+            ("user_2", Text(self.user_2.as_deref())),
+            ("user_3", Text(self.user_3.as_deref())),
+            ("user_4", Text(self.user_4.as_deref())),
+            ("user_5", Text(self.user_5.as_deref())),
+            ("custom_1", Text(self.custom_1.as_deref())),
+            ("custom_2", Text(self.custom_2.as_deref())),
+            ("custom_3", Text(self.custom_3.as_deref())),
+            ("custom_4", Text(self.custom_4.as_deref())),
+            ("custom_5", Text(self.custom_5.as_deref())),
+            ("custom_6", Text(self.custom_6.as_deref())),
+            ("custom_7", Text(self.custom_7.as_deref())),
+            ("custom_8", Text(self.custom_8.as_deref())),
+            ("misc_1", Text(self.misc_1.as_deref())),
+            ("misc_2", Text(self.misc_2.as_deref())),
+            ("misc_3", Text(self.misc_3.as_deref())),
+            ("wos_times_cited", Text(self.wos_times_cited.as_deref())),
+            ("total_times_cited", Text(self.total_times_cited.as_deref())),
+        ]
+    }
+
+    /// Append a JSON object with the reference type and all the populated fields of this entry to a string.
+    fn write_json(&self, out: &mut String) {
+        out.push_str("{\"reference_type\":");
+        out.push_str(&escape_json(&self.reference_type.to_string()));
+
+        for (name, value) in self.fields() {
+            let value = match value {
+                FieldValue::Text(Some(text)) => escape_json(text),
+                FieldValue::Date(Some(date)) => escape_json(&date.to_string()),
+                FieldValue::List(values) if !values.is_empty() => {
+                    let values: Vec<_> = values.iter().map(|v| escape_json(v)).collect();
+                    format!("[{}]", values.join(","))
+                }
+                _ => continue,
+            };
+
+            out.push_str(&format!(",\"{}\":{}", name, value));
+        }
+
+        if !self.unknown_tags.is_empty() {
+            let mut unknown_tags: Vec<(&str, Vec<String>)> = Vec::new();
+
+            for (tag, value) in &self.unknown_tags {
+                match unknown_tags.iter_mut().find(|(t, _)| t == tag) {
+                    Some((_, values)) => values.push(escape_json(value)),
+                    None => unknown_tags.push((tag, vec![escape_json(value)])),
+                }
+            }
+
+            let unknown_tags: Vec<_> = unknown_tags
+                .iter()
+                .map(|(tag, values)| format!("{}:[{}]", escape_json(tag), values.join(",")))
+                .collect();
+            out.push_str(&format!(",\"unknown_tags\":{{{}}}", unknown_tags.join(",")));
+        }
+
+        out.push('}');
+    }
+
+    /// The values of all the fields of this entry as a row of CSV cells, in the same order as the header.
+    fn csv_row(&self) -> String {
+        let mut row = escape_csv(&self.reference_type.to_string()).into_owned();
+
+        for (_, value) in self.fields() {
+            let value = match value {
+                FieldValue::Text(text) => Cow::Borrowed(text.unwrap_or_default()),
+                FieldValue::Date(date) => {
+                    Cow::Owned(date.map(ToString::to_string).unwrap_or_default())
+                }
+                FieldValue::List(values) => Cow::Owned(values.join("; ")),
+            };
+
+            row.push(',');
+            row.push_str(&escape_csv(&value));
+        }
+
+        row
+    }
+
+    fn csv_header() -> String {
+        let mut header = String::from("reference_type");
+
+        for (name, _) in Entry::new(ReferenceType::Generic).fields() {
+            header.push(',');
+            header.push_str(name);
+        }
+
+        header
+    }
+
+    /// Sort `authors` alphabetically, case-insensitively.
+    ///
+    /// Note that the order of authors is usually meaningful, since it is generally kept by citation styles:
+    /// this should only be used when the original order isn't needed.
+    pub fn sort_authors(&mut self) {
+        self.authors.sort_by_cached_key(|a| a.to_lowercase());
+    }
+
+    /// All the DOIs in the `doi` field, for aggregators which put several of them in a single `DO` tag.
+    ///
+    /// The field is split on whitespace and semicolons, and each DOI is [normalized](crate::Entry::normalized_doi).
+    pub fn all_dois(&self) -> Vec<&str> {
+        self.doi
+            .iter()
+            .flat_map(|doi| doi.split(|c: char| c == ';' || c.is_whitespace()))
+            .map(normalize_doi)
+            .filter(|doi| !doi.is_empty())
+            .collect()
+    }
+
+    /// The DOI of this entry, looking for it in `notes` and then `misc_3` if the `doi` field is missing,
+    /// since some exports put it there instead (e.g. `N1  - doi: 10.1000/182`).
+    ///
+    /// The DOI from the `doi` field is [normalized](crate::Entry::normalized_doi), while in the other fields the first
+    /// text matching the DOI syntax is returned, without any trailing punctuation. The fields themselves are left untouched.
+    pub fn extract_doi(&self) -> Option<String> {
+        if let Some(doi) = self.normalized_doi().filter(|doi| !doi.is_empty()) {
+            return Some(doi.to_owned());
+        }
+
+        IntoIterator::into_iter([&self.notes, &self.misc_3])
+            .flatten()
+            .find_map(|text| find_doi(text))
+            .map(String::from)
+    }
+
+    /// The number of populated fields, not counting `reference_type` and `unknown_tags`.
+    ///
+    /// Repeatable fields such as `authors` count as a single field, regardless of how many values they hold.
+    pub fn field_count(&self) -> usize {
+        self.fields()
+            .iter()
+            .filter(|(_, value)| value.is_set())
+            .count()
+    }
+
+    /// The fraction, between 0 and 1, of the [fields recommended](crate::ReferenceType::recommended_fields)
+    /// for the type of this entry which are present.
+    ///
+    /// Fields other than the recommended ones don't affect the score.
+    pub fn completeness(&self) -> f32 {
+        let recommended = self.reference_type.recommended_fields().len();
+        let missing = self.missing_recommended_fields().len();
+
+        if recommended == 0 {
+            1.0
+        } else {
+            (recommended - missing) as f32 / recommended as f32
+        }
+    }
+
+    /// Fill the fields missing from this entry with the values of another entry.
+    ///
+    /// Fields which are already set are left untouched, and repeatable fields such as `authors` are only filled if empty.
+    /// The reference type is never changed.
+    pub fn fill_missing_from(&mut self, other: &Entry) {
+        macro_rules! fill {
+            ($is_missing:ident => $($field:ident),* $(,)?) => {
+                $(
+                    if self.$field.$is_missing() {
+                        self.$field = other.$field.clone();
+                    }
+                )*
+            };
+        }
+
+        fill!(
+            is_empty =>
+            authors,
+            secondary_authors,
+            tertiary_authors,
+            full_authors,
+            keywords,
+            cited_references,
+            editors,
+            file_attachments,
+            urls,
+            unknown_tags,
+        );
+        fill!(
+            is_none =>
+            id,
+            title,
+            secondary_title,
+            tertiary_title,
+            short_title,
+            primary_date,
+            secondary_date,
+            notes,
+            abstract_,
+            reprint,
+            availability,
+            caption,
+            call_number,
+            classification,
+            doi,
+            accession_number,
+            start_page,
+            end_page,
+            journal,
+            journal_abbrev,
+            journal_abbrev_1,
+            journal_abbrev_2,
+            volume,
+            issue,
+            city,
+            publisher,
+            serial_number,
+            address,
+            workplace,
+            user_1,
+            user_2,
+            user_3,
+            user_4,
+            user_5,
+            custom_1,
+            custom_2,
+            custom_3,
+            custom_4,
+            custom_5,
+            custom_6,
+            custom_7,
+            custom_8,
+            misc_1,
+            misc_2,
+            misc_3,
+            wos_times_cited,
+            total_times_cited,
+        );
+    }
+
+    /// The key used by [RIS::dedup_keep_most_complete](crate::RIS::dedup_keep_most_complete) to find duplicates.
+    fn dedup_key(&self) -> Option<String> {
+        if let Some(doi) = self.normalized_doi().filter(|doi| !doi.is_empty()) {
+            return Some(format!("doi:{}", doi.to_lowercase()));
+        }
+
+        let title = self.normalized_title()?;
+        let year = self.primary_date.as_ref().map(|d| d.year);
+
+        Some(format!("title:{:?}:{}", year, title))
+    }
+
+    /// Soft data-quality warnings about implausible years in the `primary_date` and `secondary_date`,
+    /// typically caused by typos or OCR errors such as `2918` instead of `2018`.
+    ///
+    /// The following thresholds are used:
+    /// - years after the next calendar year (according to the system clock) are [in the future](crate::DateWarning::FutureYear)
+    /// - years before 1950 are [implausibly old](crate::DateWarning::ImplausiblyOld) for electronic and online resources,
+    ///   such as web pages, blogs, electronic articles and books, databases, data files and computer programs
+    /// - years before 1450, the invention of the printing press, are implausibly old for most other types
+    /// - ancient texts, classical works, manuscripts, art works, maps, and unknown types are never too old
+    pub fn date_warnings(&self) -> Vec<DateWarning> {
+        let max_year = current_year() + 1;
+        let min_year = self.reference_type.earliest_plausible_year();
+
+        [
+            ("primary_date", &self.primary_date),
+            ("secondary_date", &self.secondary_date),
+        ]
+        .iter()
+        .filter_map(|&(field, date)| {
+            let year = date.as_ref()?.year;
+
+            if year > max_year {
+                Some(DateWarning::FutureYear { field, year })
+            } else if min_year.is_some_and(|min_year| year < min_year) {
+                Some(DateWarning::ImplausiblyOld { field, year })
+            } else {
+                None
+            }
+        })
+        .collect()
+    }
+
+    /// A short, plain-text citation of this entry, such as `Shannon (1948). A Mathematical Theory of Communication.`
+    ///
+    /// Titles longer than [DEFAULT_SHORT_TITLE_THRESHOLD](crate::DEFAULT_SHORT_TITLE_THRESHOLD) characters are
+    /// replaced by the short title, if there is one.
+    /// See [citation_with_threshold](crate::Entry::citation_with_threshold) for details.
+    pub fn citation(&self) -> String {
+        self.citation_with_threshold(DEFAULT_SHORT_TITLE_THRESHOLD)
+    }
+
+    /// Like [citation](crate::Entry::citation), with a custom threshold for the short title fallback.
+    ///
+    /// The citation is made of:
+    /// 1. The last name of the first author, followed by `and` and the last name of the second author if
+    ///    there are exactly two, or by `et al.` if there are more
+    /// 2. The year of the `primary_date`, in parentheses
+    /// 3. The title, or the `short_title` if the title is longer than `threshold` characters and a short title is set
+    ///
+    /// Missing parts are left out.
+    pub fn citation_with_threshold(&self, threshold: usize) -> String {
+        let mut parts = Vec::new();
+
+        let authors = self.parsed_authors();
+        let names = match authors.as_slice() {
+            [] => None,
+            [first] => Some(first.last_name.clone()),
+            [first, second] => Some(format!("{} and {}", first.last_name, second.last_name)),
+            [first, ..] => Some(format!("{} et al.", first.last_name)),
+        };
+
+        let year = self
+            .primary_date
+            .as_ref()
+            .map(|date| format!("({})", date.year));
+
+        match (names, year) {
+            (Some(names), Some(year)) => parts.push(format!("{} {}.", names, year)),
+            (Some(names), None) => parts.push(format!("{}.", names.trim_end_matches('.'))),
+            (None, Some(year)) => parts.push(format!("{}.", year)),
+            (None, None) => {}
+        }
+
+        let title = match (&self.title, &self.short_title) {
+            (Some(title), Some(short_title)) if title.chars().count() > threshold => {
+                Some(short_title)
+            }
+            (Some(title), _) => Some(title),
+            (None, short_title) => short_title.as_ref(),
+        };
+
+        if let Some(title) = title {
+            parts.push(format!("{}.", title.trim_end_matches('.')));
+        }
+
+        parts.join(" ")
+    }
+
+    /// The page range of this entry, joined with a hyphen (e.g. `379-423`), or just the page if only one of
+    /// `start_page` and `end_page` is set.
+    pub fn page_span(&self) -> Option<String> {
+        match (&self.start_page, &self.end_page) {
+            (Some(start), Some(end)) => Some(format!("{}-{}", start, end)),
+            (Some(page), None) | (None, Some(page)) => Some(page.clone()),
+            (None, None) => None,
+        }
+    }
+
+    /// Whether no field other than `reference_type` is set, including `unknown_tags`.
+    pub fn is_empty(&self) -> bool {
+        self.field_count() == 0 && self.unknown_tags.is_empty()
+    }
+
+    /// A BibTeX-style citation key for this entry, such as `shannon1948`.
+    ///
+    /// The key is generated by:
+    /// 1. Taking the last name of the first author or, if there are no authors, the first word of the title
+    ///    which isn't `a`, `an` or `the`, or `anon` if there is no title either
+    /// 2. Lowercasing it and removing all the characters which aren't ASCII letters or digits
+    /// 3. Appending the year of the `primary_date`, if there is one
+    ///
+    /// The key is not guaranteed to be unique within a list of entries.
+    pub fn cite_key(&self) -> String {
+        let sanitize = |s: &str| -> String {
+            s.to_lowercase()
+                .chars()
+                .filter(char::is_ascii_alphanumeric)
+                .collect()
+        };
+
+        let name = self
+            .parsed_authors()
+            .first()
+            .map(|author| sanitize(&author.last_name))
+            .filter(|name| !name.is_empty())
+            .or_else(|| {
+                self.title
+                    .iter()
+                    .flat_map(|title| title.split_whitespace())
+                    .map(sanitize)
+                    .find(|word| !word.is_empty() && !["a", "an", "the"].contains(&word.as_str()))
+            })
+            .unwrap_or_else(|| String::from("anon"));
+
+        match self.primary_date {
+            Some(ref date) => format!("{}{}", name, date.year),
+            None => name,
+        }
+    }
+
+    /// Export this entry to a JSON object, without depending on serde.
+    ///
+    /// The object contains the `reference_type` of the entry and all its populated fields, keyed by field name.
+    /// Repeatable fields such as `authors` are arrays of strings, and all the other fields are strings.
+    /// Unknown tags, if any, are in an `unknown_tags` object mapping each tag to the array of its values.
+    pub fn to_json(&self) -> String {
+        let mut json = String::new();
+        self.write_json(&mut json);
+        json
+    }
+
+    /// Compare two entries chronologically by their `primary_date`, with entries without a date last.
+    ///
+    /// Dates are compared by year, then month, then day, then other info, with missing components first,
+    /// so `1948` comes before `1948/07//`.
+    pub fn cmp_by_date(&self, other: &Entry) -> Ordering {
+        match (&self.primary_date, &other.primary_date) {
+            (Some(a), Some(b)) => a.cmp(b),
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => Ordering::Equal,
+        }
+    }
+
+    /// Mutable references to all the set text values of this entry,
+    /// including the values of repeatable fields and `unknown_tags`, but not dates.
+    fn text_values_mut(&mut self) -> Vec<&mut String> {
+        let texts = [
+            &mut self.id,
+            &mut self.title,
+            &mut self.secondary_title,
+            &mut self.tertiary_title,
+            &mut self.short_title,
+            &mut self.notes,
+            &mut self.abstract_,
+            &mut self.reprint,
+            &mut self.availability,
+            &mut self.caption,
+            &mut self.call_number,
+            &mut self.classification,
+            &mut self.doi,
+            &mut self.accession_number,
+            &mut self.start_page,
+            &mut self.end_page,
+            &mut self.journal,
+            &mut self.journal_abbrev,
+            &mut self.journal_abbrev_1,
+            &mut self.journal_abbrev_2,
+            &mut self.volume,
+            &mut self.issue,
+            &mut self.city,
+            &mut self.publisher,
+            &mut self.serial_number,
+            &mut self.address,
+            &mut self.workplace,
+            &mut self.user_1,
+            &mut self.user_2,
+            &mut self.user_3,
+            &mut self.user_4,
+            &mut self.user_5,
+            &mut self.custom_1,
+            &mut self.custom_2,
+            &mut self.custom_3,
+            &mut self.custom_4,
+            &mut self.custom_5,
+            &mut self.custom_6,
+            &mut self.custom_7,
+            &mut self.custom_8,
+            &mut self.misc_1,
+            &mut self.misc_2,
+            &mut self.misc_3,
+            &mut self.wos_times_cited,
+            &mut self.total_times_cited,
+        ];
+        let lists = [
+            &mut self.authors,
+            &mut self.secondary_authors,
+            &mut self.tertiary_authors,
+            &mut self.full_authors,
+            &mut self.keywords,
+            &mut self.cited_references,
+            &mut self.editors,
+            &mut self.file_attachments,
+            &mut self.urls,
+        ];
+
+        IntoIterator::into_iter(texts)
+            .flat_map(Option::as_mut)
+            .chain(IntoIterator::into_iter(lists).flat_map(|values| values.iter_mut()))
+            .chain(self.unknown_tags.iter_mut().map(|(_, value)| value))
+            .collect()
+    }
+
+    /// Truncate all the text values of this entry, including the values of repeatable fields and `unknown_tags`,
+    /// to at most `max` characters, replacing the end of truncated values with `…`.
+    ///
+    /// This is meant for previews and tables, where long values such as abstracts would get in the way.
+    /// The ellipsis counts towards the maximum length, and values are always truncated at character boundaries.
+    pub fn truncate_fields(&mut self, max: usize) {
+        for value in self.text_values_mut() {
+            if value.chars().count() > max {
+                let end = value
+                    .char_indices()
+                    .nth(max.saturating_sub(1))
+                    .map_or(value.len(), |(i, _)| i);
+                value.truncate(end);
+
+                if max > 0 {
+                    value.push('…');
+                }
+            }
+        }
+    }
+
+    /// Clean up this entry in one pass, applying the operations enabled in the [SanitizeOptions](crate::SanitizeOptions).
+    ///
+    /// Carriage returns are stripped before values are trimmed, and both are done before the other operations,
+    /// so the DOI and keywords are normalized and compared once trimmed.
+    pub fn sanitize(&mut self, options: SanitizeOptions) {
+        if options.strip_carriage_returns || options.trim_values {
+            for value in self.text_values_mut() {
+                if options.strip_carriage_returns && value.contains('\r') {
+                    *value = value.replace("\r\n", "\n").replace('\r', "\n");
+                }
+
+                if options.trim_values && value.trim().len() != value.len() {
+                    *value = String::from(value.trim());
+                }
+            }
+        }
+
+        if options.normalize_doi {
+            if let Some(doi) = &mut self.doi {
+                *doi = String::from(normalize_doi(doi));
+            }
+        }
+
+        if options.canonicalize_type {
+            self.reference_type.canonicalize();
+        }
+
+        if options.dedup_keywords {
+            let mut seen = HashSet::new();
+            self.keywords
+                .retain(|keyword| seen.insert(keyword.to_lowercase()));
+        }
+    }
+
+    /// The article number of this entry, which electronic journals often put in `SP` instead of a page,
+    /// such as `e0234567`.
+    ///
+    /// This uses a heuristic: `start_page` is considered an article number if `end_page` is missing or empty,
+    /// and `start_page` is neither made only of digits nor a roman numeral.
+    /// Purely numeric article numbers can't be told apart from pages, and are never returned.
+    pub fn article_number(&self) -> Option<&str> {
+        let start = self.start_page.as_deref()?.trim();
+
+        let is_page = start.chars().all(|c| c.is_ascii_digit())
+            || start
+                .chars()
+                .all(|c| "ivxlcdm".contains(c.to_ascii_lowercase()));
+
+        if start.is_empty()
+            || is_page
+            || self
+                .end_page
+                .as_deref()
+                .is_some_and(|end| !end.trim().is_empty())
+        {
+            None
+        } else {
+            Some(start)
+        }
+    }
+
+    /// The titles of this entry and the works containing it which are present, from the innermost to the outermost:
+    /// `title`, then `secondary_title`, then `tertiary_title`.
+    ///
+    /// For a chapter in a book which is part of a series, this is the chapter title, the book title and the series title.
+    /// Missing and empty titles are skipped.
+    pub fn venue_hierarchy(&self) -> Vec<&str> {
+        IntoIterator::into_iter([&self.title, &self.secondary_title, &self.tertiary_title])
+            .filter_map(|title| title.as_deref())
+            .filter(|title| !title.trim().is_empty())
+            .collect()
+    }
+
+    /// The issue number of this entry: `issue` if it is set, or else `misc_1`,
+    /// where some book chapter dialects put the issue number instead.
+    ///
+    /// Empty values are skipped. The fields themselves are left untouched.
+    pub fn issue_number(&self) -> Option<&str> {
+        self.issue
+            .as_deref()
+            .filter(|issue| !issue.trim().is_empty())
+            .or_else(|| {
+                self.misc_1
+                    .as_deref()
+                    .filter(|issue| !issue.trim().is_empty())
+            })
+    }
+
+    /// Clickable links to the resource described by this entry, in order:
+    ///
+    /// 1. the DOI, as `https://doi.org/<doi>`, if it is set (see [extract_doi](crate::Entry::extract_doi))
+    /// 2. the PubMed ID, as `https://pubmed.ncbi.nlm.nih.gov/<pmid>/`, if `accession_number` holds one
+    /// 3. the links in `urls`
+    /// 4. the links in `file_attachments`
+    ///
+    /// `accession_number` is considered a PubMed ID if it is made of up to 8 digits, optionally prefixed by `PMID:`,
+    /// which is how PubMed exports it.
+    /// Duplicate links are only included once.
+    pub fn resource_urls(&self) -> Vec<String> {
+        lazy_static! {
+            static ref PMID_RE: Regex = Regex::new("^(?i:PMID:?\\s*)?(\\d{1,8})$").unwrap();
+        }
+
+        let doi = self
+            .extract_doi()
+            .map(|doi| format!("https://doi.org/{}", doi));
+        let pmid = self
+            .accession_number
+            .as_deref()
+            .and_then(|an| PMID_RE.captures(an.trim()))
+            .map(|pmid| format!("https://pubmed.ncbi.nlm.nih.gov/{}/", &pmid[1]));
+        let links = self.urls.iter().chain(&self.file_attachments).cloned();
+
+        let mut urls: Vec<String> = Vec::new();
+
+        for url in doi.into_iter().chain(pmid).chain(links) {
+            if !url.trim().is_empty() && !urls.contains(&url) {
+                urls.push(url);
+            }
+        }
+
+        urls
+    }
+
+    /// The abbreviated journal name, for consumers which don't care which of the abbreviation fields it comes from:
+    /// the first non-empty value among `journal_abbrev` (`JA`), `journal_abbrev_2` (`J2`) and `journal_abbrev_1` (`J1`).
+    ///
+    /// `J2` takes precedence over `J1` since it is the alternate title field which most tools export abbreviations to,
+    /// while `J1` is used more loosely. The fields themselves are left untouched.
+    pub fn best_journal_abbrev(&self) -> Option<&str> {
+        IntoIterator::into_iter([
+            &self.journal_abbrev,
+            &self.journal_abbrev_2,
+            &self.journal_abbrev_1,
+        ])
+        .filter_map(|abbrev| abbrev.as_deref())
+        .find(|abbrev| !abbrev.trim().is_empty())
+    }
+
+    /// The first of the `authors`, such as for citations of the form "First et al.".
+    pub fn first_author(&self) -> Option<&str> {
+        self.authors.first().map(String::as_str)
+    }
+
+    /// All the `authors` except the [first one](crate::Entry::first_author), which is empty if there are less than two.
+    pub fn other_authors(&self) -> &[String] {
+        self.authors.get(1..).unwrap_or_default()
+    }
+
+    /// The `keywords` grouped by the controlled vocabulary they come from, as given by a `scheme:` prefix
+    /// like in `MeSH: Neoplasms`, with the prefix and surrounding whitespace removed.
+    ///
+    /// A prefix is only recognized if it is a single word made of letters, digits, `-`, `_` and `.`,
+    /// and is followed by a non-empty keyword. Other keywords are grouped under `None`, as they are.
+    /// Keywords keep their order within each group. The keywords themselves are left untouched.
+    pub fn keywords_by_scheme(&self) -> BTreeMap<Option<String>, Vec<&str>> {
+        let mut groups: BTreeMap<_, Vec<_>> = BTreeMap::new();
+
+        for keyword in &self.keywords {
+            let (scheme, keyword) = match keyword.split_once(':') {
+                Some((scheme, rest))
+                    if !scheme.is_empty()
+                        && !rest.trim().is_empty()
+                        && scheme
+                            .chars()
+                            .all(|c| c.is_alphanumeric() || "-_.".contains(c)) =>
+                {
+                    (Some(scheme.to_owned()), rest.trim())
+                }
+                _ => (None, keyword.as_str()),
+            };
+
+            groups.entry(scheme).or_default().push(keyword);
+        }
+
+        groups
+    }
+
+    /// Whether any of the links in `urls` or `file_attachments` just points to the DOI of this entry,
+    /// such as `https://doi.org/10.1000/182`, so that it can be dropped as redundant.
+    ///
+    /// A link is redundant if it is a `doi.org` or `dx.doi.org` URL, over either HTTP or HTTPS, whose DOI is equal
+    /// to the entry's DOI (see [extract_doi](crate::Entry::extract_doi)) ignoring case, since DOIs are case-insensitive.
+    pub fn redundant_url(&self) -> bool {
+        let doi = match self.extract_doi() {
+            Some(doi) => doi.to_lowercase(),
+            None => return false,
+        };
+
+        self.urls.iter().chain(&self.file_attachments).any(|url| {
+            let url = url.trim();
+            let url_doi = normalize_doi(url);
+
+            url_doi.len() < url.len()
+                && url[..url.len() - url_doi.len()]
+                    .to_lowercase()
+                    .contains("doi.org")
+                && url_doi.to_lowercase() == doi
+        })
+    }
+
+    /// The paragraphs of the abstract, which are separated by blank lines (`\n\n`).
+    ///
+    /// Whitespace around each paragraph is trimmed, and empty paragraphs are skipped.
+    pub fn abstract_paragraphs(&self) -> Vec<&str> {
+        self.abstract_
+            .iter()
+            .flat_map(|abstract_| abstract_.split("\n\n"))
+            .map(str::trim)
+            .filter(|paragraph| !paragraph.is_empty())
+            .collect()
+    }
+
+    /// Set the abstract to the given paragraphs, separated by blank lines (`\n\n`),
+    /// or remove it if there are no paragraphs.
+    pub fn set_abstract_paragraphs(&mut self, paragraphs: &[&str]) {
+        self.abstract_ = if paragraphs.is_empty() {
+            None
+        } else {
+            Some(paragraphs.join("\n\n"))
+        };
+    }
+
+    /// The total number of pages, for entries such as reports which give it in `start_page` instead of a page range,
+    /// like `45 p.`.
+    ///
+    /// This uses a heuristic: `start_page` must be a number followed by `p.` or `pp.` and nothing else,
+    /// ignoring case and whitespace. Other values, including plain numbers, are considered pages rather than a count.
+    pub fn page_count(&self) -> Option<u32> {
+        lazy_static! {
+            static ref PAGE_COUNT_RE: Regex = Regex::new("^(?i)(\\d+)\\s*pp?\\.$").unwrap();
+        }
+
+        let start = self.start_page.as_deref()?.trim();
+        PAGE_COUNT_RE.captures(start)?[1].parse().ok()
+    }
+
+    /// Turn this entry into an [EntryBuilder](crate::EntryBuilder) to edit it fluently.
+    /// See [EntryBuilder::from_entry](crate::EntryBuilder::from_entry).
+    pub fn into_builder(self) -> EntryBuilder {
+        EntryBuilder::from_entry(self)
+    }
+
+    /// Whether the check digit of the `serial_number` is correct, or `None` if there is no serial number.
+    ///
+    /// The format is detected from the number of digits once hyphens and spaces are removed:
+    /// 8 for an ISSN, 10 for an ISBN-10 and 13 for an ISBN-13. The check digit of ISSNs and ISBN-10s can be `X`.
+    /// Serial numbers in any other format are invalid.
+    pub fn serial_number_is_valid(&self) -> Option<bool> {
+        let serial_number = self.serial_number.as_deref()?;
+        let chars: Vec<char> = serial_number
+            .chars()
+            .filter(|c| *c != '-' && !c.is_whitespace())
+            .collect();
+
+        let digits: Option<Vec<u32>> = chars
+            .iter()
+            .enumerate()
+            .map(|(i, c)| match c {
+                'X' | 'x' if i == chars.len() - 1 && chars.len() != 13 => Some(10),
+                _ => c.to_digit(10),
+            })
+            .collect();
+
+        let digits = match digits {
+            Some(digits) => digits,
+            None => return Some(false),
+        };
+
+        let weighted_sum = |weight: &dyn Fn(usize) -> u32| -> u32 {
+            digits.iter().enumerate().map(|(i, d)| weight(i) * d).sum()
+        };
+
+        Some(match digits.len() {
+            8 => weighted_sum(&|i| 8 - i as u32) % 11 == 0,
+            10 => weighted_sum(&|i| 10 - i as u32) % 11 == 0,
+            13 => weighted_sum(&|i| if i % 2 == 0 { 1 } else { 3 }) % 10 == 0,
+            _ => false,
+        })
+    }
+
+    /// An empty entry of the given type, to be filled in with the fields in [required_fields](crate::Entry::required_fields),
+    /// such as to generate a form for it.
+    ///
+    /// No field is set, as with [new](crate::Entry::new): the type only determines which fields are needed.
+    pub fn template(reference_type: ReferenceType) -> Entry {
+        Entry::new(reference_type)
+    }
+
+    /// The names of the fields that an entry of this type needs to be cited,
+    /// as given by [ReferenceType::recommended_fields](crate::ReferenceType::recommended_fields).
+    ///
+    /// See [missing_recommended_fields](crate::Entry::missing_recommended_fields) for the ones which aren't set.
+    pub fn required_fields(&self) -> &'static [&'static str] {
+        self.reference_type.recommended_fields()
+    }
+
+    /// All the tags of this entry with their values, in the order they are serialized in, from `TY` to the
+    /// [unknown_tags](crate::Entry::unknown_tags), and excluding `ER`.
+    ///
+    /// Repeatable fields give one tag for each value, and dates are formatted as in RIS.
+    pub fn iter_tags(&self) -> impl Iterator<Item = (&str, Cow<'_, str>)> {
+        self.tags(&SerializeOptions::default()).into_iter()
+    }
+
+    /// All the tags of this entry, as in [iter_tags](crate::Entry::iter_tags), mapped to their values in order.
+    pub fn to_map(&self) -> BTreeMap<String, Vec<String>> {
+        let mut map: BTreeMap<String, Vec<String>> = BTreeMap::new();
+
+        for (tag, value) in self.iter_tags() {
+            map.entry(tag.to_owned())
+                .or_default()
+                .push(value.into_owned());
+        }
+
+        map
+    }
+}
+
+/// The length in characters above which [Entry::citation](crate::Entry::citation) uses the short title instead of the title.
+pub const DEFAULT_SHORT_TITLE_THRESHOLD: usize = 80;
+
+/// A borrowed view of the value of a field of an [Entry](crate::Entry).
+#[derive(Debug, Clone, Copy)]
+enum FieldValue<'a> {
+    Text(Option<&'a str>),
+    Date(Option<&'a PublicationDate>),
+    List(&'a [String]),
+}
+
+impl FieldValue<'_> {
+    fn is_set(&self) -> bool {
+        match self {
+            FieldValue::Text(value) => value.is_some(),
+            FieldValue::Date(value) => value.is_some(),
+            FieldValue::List(values) => !values.is_empty(),
+        }
+    }
+}
+
+/// Concisely build an [Entry](crate::Entry).
+///
+/// The reference type is given first, with either a [ReferenceType](crate::ReferenceType) variant or any expression of that type.
+/// It is followed by any number of `field: value` pairs, where:
+///
+/// - `year` sets the `primary_date` to the given year
+/// - `authors`, `secondary_authors`, `editors`, `tertiary_authors`, `full_authors`, `keywords`, `cited_references`, `urls`
+///   and `file_attachments` accept anything iterable over strings
+/// - any other field accepts a string, and is set to it
+///
+/// ```
+/// use ris::{ris_entry, Entry, PublicationDate, ReferenceType};
+///
+/// let entry = ris_entry! {
+///     type: Journal,
+///     title: "A Mathematical Theory of Communication",
+///     authors: ["Shannon, Claude E."],
+///     year: 1948,
+///     volume: "27",
+/// };
+///
+/// assert_eq!(
+///     entry,
+///     Entry {
+///         title: Some(String::from("A Mathematical Theory of Communication")),
+///         authors: vec![String::from("Shannon, Claude E.")],
+///         primary_date: Some(PublicationDate::new(1948, None, None, None)),
+///         volume: Some(String::from("27")),
+///         ..Entry::new(ReferenceType::Journal)
+///     }
+/// );
+///
+/// let other = ris_entry! { type: Other(String::from("ODD")) };
+/// assert_eq!(other.reference_type, ReferenceType::Other(String::from("ODD")));
+/// ```
+#[macro_export]
+macro_rules! ris_entry {
+    (type: $reference_type:expr $(, $field:ident : $value:expr)* $(,)?) => {{
+        #[allow(unused_mut)]
+        let mut entry = $crate::Entry::new({
+            #[allow(unused_imports)]
+            use $crate::ReferenceType::*;
+            $reference_type
+        });
+
+        $($crate::ris_entry!(@set entry, $field, $value);)*
+
+        entry
+    }};
+
+    (@set $entry:ident, year, $value:expr) => {
+        $entry.primary_date = Some($crate::PublicationDate::new($value, None, None, None));
+    };
+    (@set $entry:ident, authors, $value:expr) => {
+        $crate::ris_entry!(@extend $entry.authors, $value);
+    };
+    (@set $entry:ident, secondary_authors, $value:expr) => {
+        $crate::ris_entry!(@extend $entry.secondary_authors, $value);
+    };
+    (@set $entry:ident, editors, $value:expr) => {
+        $crate::ris_entry!(@extend $entry.editors, $value);
+    };
+    (@set $entry:ident, tertiary_authors, $value:expr) => {
+        $crate::ris_entry!(@extend $entry.tertiary_authors, $value);
+    };
+    (@set $entry:ident, full_authors, $value:expr) => {
+        $crate::ris_entry!(@extend $entry.full_authors, $value);
+    };
+    (@set $entry:ident, keywords, $value:expr) => {
+        $crate::ris_entry!(@extend $entry.keywords, $value);
+    };
+    (@set $entry:ident, cited_references, $value:expr) => {
+        $crate::ris_entry!(@extend $entry.cited_references, $value);
+    };
+    (@set $entry:ident, urls, $value:expr) => {
+        $crate::ris_entry!(@extend $entry.urls, $value);
+    };
+    (@set $entry:ident, file_attachments, $value:expr) => {
+        $crate::ris_entry!(@extend $entry.file_attachments, $value);
+    };
+    (@set $entry:ident, $field:ident, $value:expr) => {
+        $entry.$field = Some(::std::string::String::from($value));
+    };
+
+    (@extend $vec:expr, $value:expr) => {
+        $vec.extend(::std::iter::IntoIterator::into_iter($value).map(::std::string::String::from));
+    };
+}
+
+/// A fluent builder for [Entry](crate::Entry).
+///
+/// ```
+/// use ris::{Entry, EntryBuilder, ReferenceType};
+///
+/// let entry: Entry = EntryBuilder::new(ReferenceType::Journal)
+///     .title("A Mathematical Theory of Communication")
+///     .author("Shannon, Claude E.")
+///     .year(1948)
+///     .build();
+///
+/// assert_eq!(entry.title.as_deref(), Some("A Mathematical Theory of Communication"));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct EntryBuilder {
+    entry: Entry,
+}
+
+macro_rules! builder_setters {
+    ($($field:ident),* $(,)?) => {
+        $(
+            #[doc = concat!("Set `", stringify!($field), "`.")]
+            pub fn $field(mut self, value: impl Into<String>) -> Self {
+                self.entry.$field = Some(value.into());
+                self
+            }
+        )*
+    };
+}
+
+impl EntryBuilder {
+    /// Start building an entry of the given type, with no fields set.
+    pub fn new(reference_type: ReferenceType) -> Self {
+        Self {
+            entry: Entry::new(reference_type),
+        }
+    }
+
+    /// Start building from an existing entry, keeping all its fields, to edit it.
+    ///
+    /// Setters for single fields replace their value, while the ones for repeatable fields such as
+    /// [author](crate::EntryBuilder::author) add to the existing values.
+    pub fn from_entry(entry: Entry) -> Self {
+        Self { entry }
+    }
+
+    builder_setters!(
+        id,
+        title,
+        secondary_title,
+        tertiary_title,
+        short_title,
+        notes,
+        abstract_,
+        reprint,
+        availability,
+        caption,
+        call_number,
+        classification,
+        doi,
+        accession_number,
+        start_page,
+        end_page,
+        journal,
+        journal_abbrev,
+        journal_abbrev_1,
+        journal_abbrev_2,
+        volume,
+        issue,
+        city,
+        publisher,
+        serial_number,
+        address,
+        workplace,
+        user_1,
+        user_2,
+        user_3,
+        user_4,
+        user_5,
+        custom_1,
+        custom_2,
+        custom_3,
+        custom_4,
+        custom_5,
+        custom_6,
+        custom_7,
+        custom_8,
+        misc_1,
+        misc_2,
+        misc_3,
+        wos_times_cited,
+        total_times_cited,
+    );
+
+    /// Add an author to `authors`.
+    pub fn author(mut self, value: impl Into<String>) -> Self {
+        self.entry.authors.push(value.into());
+        self
+    }
+
+    /// Add an author to `secondary_authors`.
+    pub fn secondary_author(mut self, value: impl Into<String>) -> Self {
+        self.entry.secondary_authors.push(value.into());
+        self
+    }
+
+    /// Add an editor to `editors`.
+    pub fn editor(mut self, value: impl Into<String>) -> Self {
+        self.entry.editors.push(value.into());
+        self
+    }
+
+    /// Add an author to `tertiary_authors`.
+    pub fn tertiary_author(mut self, value: impl Into<String>) -> Self {
+        self.entry.tertiary_authors.push(value.into());
+        self
+    }
+
+    /// Add an author to `full_authors`.
+    pub fn full_author(mut self, value: impl Into<String>) -> Self {
+        self.entry.full_authors.push(value.into());
+        self
+    }
+
+    /// Add a keyword to `keywords`.
+    pub fn keyword(mut self, value: impl Into<String>) -> Self {
+        self.entry.keywords.push(value.into());
+        self
+    }
+
+    /// Add a reference to `cited_references`.
+    pub fn cited_reference(mut self, value: impl Into<String>) -> Self {
+        self.entry.cited_references.push(value.into());
+        self
+    }
+
+    /// Add a URL to `urls`.
+    pub fn url(mut self, value: impl Into<String>) -> Self {
+        self.entry.urls.push(value.into());
+        self
+    }
+
+    /// Add a link to `file_attachments`.
+    pub fn file_attachment(mut self, value: impl Into<String>) -> Self {
+        self.entry.file_attachments.push(value.into());
+        self
+    }
+
+    /// Set `primary_date`.
+    pub fn primary_date(mut self, date: PublicationDate) -> Self {
+        self.entry.primary_date = Some(date);
+        self
+    }
+
+    /// Set `secondary_date`.
+    pub fn secondary_date(mut self, date: PublicationDate) -> Self {
+        self.entry.secondary_date = Some(date);
+        self
+    }
+
+    /// Set `primary_date` to the given year.
+    pub fn year(self, year: i32) -> Self {
+        self.primary_date(PublicationDate::new(year, None, None, None))
+    }
+
+    /// Finish building the entry, without any validation.
+    pub fn build(self) -> Entry {
+        self.entry
+    }
+
+    /// Finish building the entry, checking that:
+    /// 1. All the [fields recommended](crate::ReferenceType::recommended_fields) for its type are set,
+    ///    as reported by [Entry::missing_recommended_fields](crate::Entry::missing_recommended_fields)
+    /// 2. The `primary_date` and `secondary_date`, if set, are [valid](crate::PublicationDate::is_valid)
+    pub fn try_build(self) -> Result<Entry, BuildError> {
+        let missing = self.entry.missing_recommended_fields();
+
+        if !missing.is_empty() {
+            return Err(BuildError::MissingFields(missing));
+        }
+
+        for (field, date) in [
+            ("primary_date", &self.entry.primary_date),
+            ("secondary_date", &self.entry.secondary_date),
+        ]
+        .iter()
+        {
+            if date.as_ref().is_some_and(|date| !date.is_valid()) {
+                return Err(BuildError::InvalidDate(field));
+            }
+        }
+
+        Ok(self.entry)
+    }
+}
+
+impl From<EntryBuilder> for Entry {
+    fn from(builder: EntryBuilder) -> Self {
+        builder.build()
+    }
+}
+
+/// An error occurring when validating an entry with [EntryBuilder::try_build](crate::EntryBuilder::try_build).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum BuildError {
+    /// Some fields recommended for the type of the entry are missing.
+    MissingFields(Vec<&'static str>),
+    /// The date in the given field has an out of range month or day.
+    InvalidDate(&'static str),
+}
+
+impl Display for BuildError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            BuildError::MissingFields(fields) => {
+                write!(f, "Missing fields: {}", fields.join(", "))
+            }
+            BuildError::InvalidDate(field) => write!(f, "Invalid date in `{}`", field),
+        }
+    }
+}
+
+impl std::error::Error for BuildError {}
+
+/// The `n`th (starting from 1) suffix in the sequence `a`, `b`, ..., `z`, `aa`, `ab`, ...
+fn alphabetic_suffix(mut n: usize) -> String {
+    let mut suffix = Vec::new();
+
+    while n > 0 {
+        n -= 1;
+        suffix.push(b'a' + (n % 26) as u8);
+        n /= 26;
+    }
+
+    suffix.reverse();
+    String::from_utf8(suffix).unwrap()
+}
+
+/// Quote and escape a string as a JSON string literal.
+fn escape_json(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len() + 2);
+    escaped.push('"');
+
+    for c in s.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if c.is_control() => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+
+    escaped.push('"');
+    escaped
+}
+
+/// Quote a CSV cell if it contains commas, double quotes or line breaks, doubling any double quotes.
+fn escape_csv(s: &str) -> Cow<'_, str> {
+    if s.contains(&[',', '"', '\n', '\r'][..]) {
+        Cow::Owned(format!("\"{}\"", s.replace('"', "\"\"")))
+    } else {
+        Cow::Borrowed(s)
+    }
+}
+
+/// Strip the common prefixes from a DOI.
+fn normalize_doi(doi: &str) -> &str {
+    lazy_static! {
+        static ref DOI_PREFIX_RE: Regex =
+            Regex::new("^(?i:(?:https?://(?:dx\\.)?doi\\.org/|doi:)\\s*)").unwrap();
+    }
+
+    let doi = doi.trim();
+
+    match DOI_PREFIX_RE.find(doi) {
+        Some(prefix) => &doi[prefix.end()..],
+        None => doi,
+    }
+}
+
+/// The first text matching the DOI syntax in a string, without any trailing punctuation.
+fn find_doi(text: &str) -> Option<&str> {
+    lazy_static! {
+        static ref EMBEDDED_DOI_RE: Regex =
+            Regex::new("10\\.\\d{4,}(?:\\.\\d+)*/[^\\s;,]+").unwrap();
+    }
+
+    EMBEDDED_DOI_RE
+        .find(text)
+        .map(|doi| doi.as_str().trim_end_matches(&['.', ')', ']'][..]))
+}
+
+/// Lowercase a string, keep only its alphanumeric characters and whitespace, and collapse runs of whitespace,
+/// as described in [Entry::normalized_title](crate::Entry::normalized_title).
+fn normalize_text(s: &str) -> String {
+    let s: String = s
+        .to_lowercase()
+        .chars()
+        .filter(|c| c.is_alphanumeric() || c.is_whitespace())
+        .collect();
+
+    s.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn is_valid_doi(doi: &str) -> bool {
+    lazy_static! {
+        static ref DOI_RE: Regex = Regex::new("^10\\.\\d{4,}(?:\\.\\d+)*/\\S+$").unwrap();
+    }
+
+    DOI_RE.is_match(doi)
+}
+
+impl FromStr for Entry {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut partial = PartialEntry::new();
+        let mut line_no = 0;
+
+        for line in trim_bom(s).lines() {
+            line_no += 1;
+            partial.parse_line(line, line_no, &ParseOptions::default())?;
+        }
+
+        if partial.state == ParseState::End {
+            Ok(partial.entry.unwrap())
+        } else {
+            Err(ParseError::new(line_no, ParseErrorKind::UnterminatedEntry))
+        }
+    }
+}
+
+impl Display for Entry {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        self.write_ris(f, &SerializeOptions::default())
+    }
+}
+
+impl Entry {
+    /// The tags of this entry and their values, in the order they are serialized in, starting with `TY`
+    /// and excluding `ER`.
+    fn tags(&self, options: &SerializeOptions) -> Vec<(&str, Cow<'_, str>)> {
+        let mut tags = vec![("TY", Cow::Owned(self.reference_type.to_string()))];
+
+        if options.emit_id {
+            push_tag(&mut tags, "ID", &self.id);
+        }
+
+        push_tag(&mut tags, "T1", &self.title);
+        push_tag(&mut tags, "T2", &self.secondary_title);
+        push_tag(&mut tags, "T3", &self.tertiary_title);
+        push_tag(&mut tags, "ST", &self.short_title);
+
+        push_tags(&mut tags, "A1", &self.authors);
+        push_tags(&mut tags, "A2", &self.secondary_authors);
+        push_tags(&mut tags, "ED", &self.editors);
+        push_tags(&mut tags, "A3", &self.tertiary_authors);
+        push_tags(&mut tags, "AF", &self.full_authors);
+
+        match &self.primary_date {
+            Some(date)
+                if options.primary_date_tag == Y1OrPy::Py
+                    && date.month.is_none()
+                    && date.day.is_none()
+                    && date.other_info.as_deref().unwrap_or_default().is_empty() =>
+            {
+                tags.push(("PY", Cow::Owned(format!("{:04}", date.year))))
+            }
+            _ => push_date(&mut tags, options, "Y1", &self.primary_date),
+        }
+        push_date(&mut tags, options, "Y2", &self.secondary_date);
+
+        push_tag(&mut tags, "N1", &self.notes);
+        push_tag(&mut tags, "AB", &self.abstract_);
+
+        push_tags(&mut tags, "KW", &self.keywords);
+        push_tags(&mut tags, "CR", &self.cited_references);
+
+        push_tag(&mut tags, "RP", &self.reprint);
+        push_tag(&mut tags, "AV", &self.availability);
+        push_tag(&mut tags, "CA", &self.caption);
+        push_tag(&mut tags, "CN", &self.call_number);
+        push_tag(&mut tags, "CL", &self.classification);
+        push_tag(&mut tags, "DO", &self.doi);
+        push_tag(&mut tags, "AN", &self.accession_number);
+        push_tags(&mut tags, "UR", &self.urls);
+        push_tags(&mut tags, "L1", &self.file_attachments);
+
+        push_tag(&mut tags, "SP", &self.start_page);
+        push_tag(&mut tags, "EP", &self.end_page);
+
+        push_tag(&mut tags, "JF", &self.journal);
+        push_tag(&mut tags, "JA", &self.journal_abbrev);
+        push_tag(&mut tags, "J1", &self.journal_abbrev_1);
+        push_tag(&mut tags, "J2", &self.journal_abbrev_2);
+
+        push_tag(&mut tags, "VL", &self.volume);
+        push_tag(&mut tags, "IS", &self.issue);
+        push_tag(&mut tags, "CY", &self.city);
+        push_tag(&mut tags, "PB", &self.publisher);
+        push_tag(&mut tags, "SN", &self.serial_number);
+        push_tag(&mut tags, "AD", &self.address);
+        push_tag(&mut tags, "WP", &self.workplace);
+
+        push_tag(&mut tags, "U1", &self.user_1);
+        push_tag(&mut tags, "U2", &self.user_2);
+        push_tag(&mut tags, "U3", &self.user_3);
+        push_tag(&mut tags, "U4", &self.user_4);
+        push_tag(&mut tags, "U5", &self.user_5);
+
+        push_tag(&mut tags, "C1", &self.custom_1);
+        push_tag(&mut tags, "C2", &self.custom_2);
+        push_tag(&mut tags, "C3", &self.custom_3);
+        push_tag(&mut tags, "C4", &self.custom_4);
+        push_tag(&mut tags, "C5", &self.custom_5);
+        push_tag(&mut tags, "C6", &self.custom_6);
+        push_tag(&mut tags, "C7", &self.custom_7);
+        push_tag(&mut tags, "C8", &self.custom_8);
+
+        push_tag(&mut tags, "M1", &self.misc_1);
+        push_tag(&mut tags, "M2", &self.misc_2);
+        push_tag(&mut tags, "M3", &self.misc_3);
+
+        push_tag(&mut tags, "TC", &self.wos_times_cited);
+        push_tag(&mut tags, "Z9", &self.total_times_cited);
+
+        tags.extend(
+            self.unknown_tags
+                .iter()
+                .map(|(tag, value)| (tag.as_str(), Cow::Borrowed(value.as_str()))),
+        );
+
+        tags
+    }
+
+    fn write_ris<W: fmt::Write>(&self, f: &mut W, options: &SerializeOptions) -> fmt::Result {
+        for (tag, value) in self.tags(options) {
+            write_line(f, options, tag, &value)?;
+        }
+
+        write!(f, "ER  - ")?;
+
+        Ok(())
+    }
+}
+
+#[inline(always)]
+fn push_tag<'a>(tags: &mut Vec<(&'a str, Cow<'a, str>)>, tag: &'a str, field: &'a Option<String>) {
+    if let Some(value) = field {
+        tags.push((tag, Cow::Borrowed(value)));
+    }
+}
+
+#[inline(always)]
+fn push_tags<'a>(tags: &mut Vec<(&'a str, Cow<'a, str>)>, tag: &'a str, field: &'a [String]) {
+    tags.extend(
+        field
+            .iter()
+            .map(|value| (tag, Cow::Borrowed(value.as_str()))),
+    );
+}
+
+#[inline(always)]
+fn write_line<W: fmt::Write, T: Display>(
+    f: &mut W,
+    options: &SerializeOptions,
+    tag: &str,
+    value: &T,
+) -> fmt::Result {
+    write!(f, "{}  - {}{}", tag, value, options.line_ending)
+}
+
+fn push_date<'a>(
+    tags: &mut Vec<(&'a str, Cow<'a, str>)>,
+    options: &SerializeOptions,
+    tag: &'a str,
+    date: &Option<PublicationDate>,
+) {
+    if let Some(date) = date {
+        let mut value = String::new();
+        date.write(&mut value, !options.unpadded_dates).unwrap();
+        tags.push((tag, Cow::Owned(value)));
+    }
+}
+
+/// The cleanup operations applied by [Entry::sanitize](crate::Entry::sanitize).
+///
+/// All the operations are enabled by default.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SanitizeOptions {
+    /// Trim the leading and trailing whitespace of all the text values, including the values of repeatable fields
+    /// and `unknown_tags`.
+    pub trim_values: bool,
+    /// Replace the `\r\n` and lone `\r` line breaks of all the text values with `\n`, as left by files with
+    /// Windows or classic Mac OS line endings.
+    pub strip_carriage_returns: bool,
+    /// Replace the DOI with its [normalized](crate::Entry::normalized_doi) form, without any URL or `doi:` prefix.
+    pub normalize_doi: bool,
+    /// [Canonicalize](crate::ReferenceType::canonicalize) the reference type, so that `Other` types which are
+    /// standard abbreviations become the corresponding variant.
+    pub canonicalize_type: bool,
+    /// Remove repeated keywords, compared case-insensitively, keeping the first occurrence of each.
+    pub dedup_keywords: bool,
+}
+
+impl Default for SanitizeOptions {
+    fn default() -> Self {
+        SanitizeOptions {
+            trim_values: true,
+            strip_carriage_returns: true,
+            normalize_doi: true,
+            canonicalize_type: true,
+            dedup_keywords: true,
+        }
+    }
+}
+
+/// Options to adapt the serialized RIS to the requirements of different importers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SerializeOptions {
+    /// The line ending to terminate lines with.
+    pub line_ending: LineEnding,
+    /// Whether to write the month and day of dates without leading zeros (e.g. `1995/3/1/`),
+    /// for importers which don't expect them. Dates are zero-padded by default.
+    pub unpadded_dates: bool,
+    /// Whether to write the `ID` tag. Some importers assign their own IDs and reject entries which already have one.
+    /// IDs are written by default.
+    pub emit_id: bool,
+    /// The tag to write the `primary_date` with.
+    pub primary_date_tag: Y1OrPy,
+    /// Whether to terminate the final `ER` of a multi-entry RIS with a line ending, for importers which expect
+    /// files to end with a newline. Nothing is written after the final `ER` by default.
+    pub trailing_newline: bool,
+}
+
+impl Default for SerializeOptions {
+    fn default() -> Self {
+        SerializeOptions {
+            line_ending: LineEnding::default(),
+            unpadded_dates: false,
+            emit_id: true,
+            primary_date_tag: Y1OrPy::default(),
+            trailing_newline: false,
+        }
+    }
+}
+
+/// The tag to write the `primary_date` of entries with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Y1OrPy {
+    /// Always write `Y1`, such as `Y1  - 1948///`. This is the default.
+    #[default]
+    Y1,
+    /// Write a bare year with `PY` if the date only has a year, such as `PY  - 1948`, and `Y1` otherwise,
+    /// for importers which expect `PY`.
+    Py,
+}
+
+/// A style of line ending.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum LineEnding {
+    /// `\n`, the default.
+    #[default]
+    Lf,
+    /// `\r\n`, as expected by some Windows importers.
+    CrLf,
+}
+
+impl Display for LineEnding {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.write_str(match self {
+            LineEnding::Lf => "\n",
+            LineEnding::CrLf => "\r\n",
+        })
+    }
+}
+
+/// The type of a reference.
+///
+/// This type implements [Display](std::fmt::Display) and [FromStr](std::str::FromStr) to (de)serialize to/from strings.
+///
+/// # Abbreviations
+///
+/// This enum encodes standard abbreviations in its variants according to the table below.
+/// If the type of a reference doesn't match any of the below abbreviations, it is encoded in the `Other` variant.
+///
+/// Abbreviations are parsed case-insensitively, since some files write them in lowercase (e.g. `TY  - jour`),
+/// and are always serialized in uppercase. The contents of `Other` are kept as they are.
+///
+/// | Abbreviation | Variant                 |
+/// |--------------|-------------------------|
+/// | ABST         | `Abstract`              |
+/// | ADVS         | `AudiovisualMaterial`   |
+/// | AGGR         | `AggregatedDatabase`    |
+/// | ANCIENT      | `AncientText`           |
+/// | ART          | `ArtWork`               |
+/// | BILL         | `Bill`                  |
+/// | BLOG         | `Blog`                  |
+/// | BOOK         | `WholeBook`             |
+/// | CASE         | `Case`                  |
+/// | CHAP         | `BookChapter`           |
+/// | CHART        | `Chart`                 |
+/// | CLSWK        | `ClassicalWork`         |
+/// | COMP         | `ComputerProgram`       |
+/// | CONF         | `ConferenceProceeding`  |
+/// | CPAPER       | `ConferencePaper`       |
+/// | CTLG         | `Catalog`               |
+/// | DATA         | `DataFile`              |
+/// | DBASE        | `OnlineDatabase`        |
+/// | DICT         | `Dictionary`            |
+/// | EBOOK        | `ElectronicBook`        |
+/// | ECHAP        | `ElectronicBookSection` |
+/// | EDBOOK       | `EditedBook`            |
+/// | EJOUR        | `ElectronicArticle`     |
+/// | ELEC         | `WebPage`               |
+/// | ENCYC        | `Encyclopedia`          |
+/// | EQUA         | `Equation`              |
+/// | FIGURE       | `Figure`                |
+/// | GEN          | `Generic`               |
+/// | GOVDOC       | `GovernmentDocument`    |
+/// | GRANT        | `Grant`                 |
+/// | HEAR         | `Hearing`               |
+/// | ICOMM        | `InternetCommunication` |
+/// | INPR         | `InPress`               |
+/// | JFULL        | `JournalFull`           |
+/// | JOUR         | `Journal`               |
+/// | LEGAL        | `LegalRuleOrRegulation` |
+/// | MANSCPT      | `Manuscript`            |
+/// | MAP          | `Map`                   |
+/// | MGZN         | `MagazineArticle`       |
+/// | MPCT         | `MotionPicture`         |
+/// | MULTI        | `OnlineMultimedia`      |
+/// | MUSIC        | `MusicScore`            |
+/// | NEWS         | `Newspaper`             |
+/// | PAMP         | `Pamphlet`              |
+/// | PAT          | `Patent`                |
+/// | PCOMM        | `PersonalCommunication` |
+/// | RPRT         | `Report`                |
+/// | SER          | `SerialPublication`     |
+/// | SLIDE        | `Slide`                 |
+/// | SOUND        | `SoundRecording`        |
+/// | STAND        | `Standard`              |
+/// | STAT         | `Statute`               |
+/// | THES         | `ThesisOrDissertation`  |
+/// | UNPB         | `UnpublishedWork`       |
+/// | VIDEO        | `VideoRecording`        |
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ReferenceType {
+    Abstract,
+    AudiovisualMaterial,
+    AggregatedDatabase,
+    AncientText,
+    ArtWork,
+    Bill,
+    Blog,
+    WholeBook,
+    Case,
+    BookChapter,
+    Chart,
+    ClassicalWork,
+    ComputerProgram,
+    ConferenceProceeding,
+    ConferencePaper,
+    Catalog,
+    DataFile,
+    OnlineDatabase,
+    Dictionary,
+    ElectronicBook,
+    ElectronicBookSection,
+    EditedBook,
+    ElectronicArticle,
+    WebPage,
+    Encyclopedia,
+    Equation,
+    Figure,
+    Generic,
+    GovernmentDocument,
+    Grant,
+    Hearing,
+    InternetCommunication,
+    InPress,
+    JournalFull,
+    Journal,
+    LegalRuleOrRegulation,
+    Manuscript,
+    Map,
+    MagazineArticle,
+    MotionPicture,
+    OnlineMultimedia,
+    MusicScore,
+    Newspaper,
+    Pamphlet,
+    Patent,
+    PersonalCommunication,
+    Report,
+    SerialPublication,
+    Slide,
+    SoundRecording,
+    Standard,
+    Statute,
+    ThesisOrDissertation,
+    UnpublishedWork,
+    VideoRecording,
+    Other(String),
+}
+
+impl ReferenceType {
+    /// The names of the [Entry](crate::Entry) fields which references of this type are recommended to have.
+    ///
+    /// This is a rough guideline based on what common citation styles need to render a reference of this type,
+    /// not a requirement of the RIS format.
+    pub fn recommended_fields(&self) -> &'static [&'static str] {
+        use ReferenceType::*;
+
+        match self {
+            Journal | MagazineArticle | Newspaper | ElectronicArticle | InPress => {
+                &["title", "authors", "primary_date", "journal"]
+            }
+            WholeBook | EditedBook | ElectronicBook | ClassicalWork => {
+                &["title", "authors", "primary_date", "publisher"]
+            }
+            BookChapter | ElectronicBookSection => &[
+                "title",
+                "authors",
+                "primary_date",
+                "secondary_title",
+                "publisher",
+            ],
+            ConferencePaper | ConferenceProceeding => {
+                &["title", "authors", "primary_date", "secondary_title"]
+            }
+            ThesisOrDissertation | Report => &["title", "authors", "primary_date", "publisher"],
+            Patent => &["title", "authors", "primary_date"],
+            _ => &["title"],
+        }
+    }
+
+    /// The reference type with the given EndNote numeric code, as used for instance in EndNote XML exports.
+    ///
+    /// See [to_endnote_code](crate::ReferenceType::to_endnote_code) for the mapping.
+    pub fn from_endnote_code(code: u32) -> Option<ReferenceType> {
+        ENDNOTE_CODES
+            .iter()
+            .find(|(_, c)| *c == code)
+            .map(|(reference_type, _)| reference_type.clone())
+    }
+
+    /// The EndNote numeric code of this reference type, as used for instance in EndNote XML exports.
+    ///
+    /// `Other` and the types which have no EndNote equivalent (`Abstract`, `InPress`, `InternetCommunication`,
+    /// `JournalFull`, `Slide`, `SoundRecording` and `VideoRecording`) have no code.
+    ///
+    /// | Variant                 | Code | EndNote name             |
+    /// |-------------------------|------|--------------------------|
+    /// | `AncientText`           | 51   | Ancient Text             |
+    /// | `ArtWork`               | 2    | Artwork                  |
+    /// | `AudiovisualMaterial`   | 3    | Audiovisual Material     |
+    /// | `AggregatedDatabase`    | 55   | Aggregated Database      |
+    /// | `Bill`                  | 4    | Bill                     |
+    /// | `Blog`                  | 56   | Blog                     |
+    /// | `WholeBook`             | 6    | Book                     |
+    /// | `BookChapter`           | 5    | Book Section             |
+    /// | `Case`                  | 7    | Case                     |
+    /// | `Catalog`               | 8    | Catalog                  |
+    /// | `Chart`                 | 38   | Chart or Table           |
+    /// | `ClassicalWork`         | 49   | Classical Work           |
+    /// | `ComputerProgram`       | 9    | Computer Program         |
+    /// | `ConferencePaper`       | 47   | Conference Paper         |
+    /// | `ConferenceProceeding`  | 10   | Conference Proceedings   |
+    /// | `DataFile`              | 59   | Dataset                  |
+    /// | `Dictionary`            | 52   | Dictionary               |
+    /// | `EditedBook`            | 28   | Edited Book              |
+    /// | `ElectronicArticle`     | 43   | Electronic Article       |
+    /// | `ElectronicBook`        | 44   | Electronic Book          |
+    /// | `ElectronicBookSection` | 60   | Electronic Book Section  |
+    /// | `Encyclopedia`          | 53   | Encyclopedia             |
+    /// | `Equation`              | 39   | Equation                 |
+    /// | `Figure`                | 37   | Figure                   |
+    /// | `MotionPicture`         | 21   | Film or Broadcast        |
+    /// | `Generic`               | 13   | Generic                  |
+    /// | `GovernmentDocument`    | 46   | Government Document      |
+    /// | `Grant`                 | 54   | Grant                    |
+    /// | `Hearing`               | 14   | Hearing                  |
+    /// | `Journal`               | 17   | Journal Article          |
+    /// | `LegalRuleOrRegulation` | 50   | Legal Rule or Regulation |
+    /// | `MagazineArticle`       | 19   | Magazine Article         |
+    /// | `Manuscript`            | 36   | Manuscript               |
+    /// | `Map`                   | 20   | Map                      |
+    /// | `MusicScore`            | 61   | Music                    |
+    /// | `Newspaper`             | 23   | Newspaper Article        |
+    /// | `OnlineDatabase`        | 45   | Online Database          |
+    /// | `OnlineMultimedia`      | 48   | Online Multimedia        |
+    /// | `Pamphlet`              | 24   | Pamphlet                 |
+    /// | `Patent`                | 25   | Patent                   |
+    /// | `PersonalCommunication` | 26   | Personal Communication   |
+    /// | `Report`                | 27   | Report                   |
+    /// | `SerialPublication`     | 57   | Serial                   |
+    /// | `Standard`              | 58   | Standard                 |
+    /// | `Statute`               | 31   | Statute                  |
+    /// | `ThesisOrDissertation`  | 32   | Thesis                   |
+    /// | `UnpublishedWork`       | 34   | Unpublished Work         |
+    /// | `WebPage`               | 12   | Web Page                 |
+    pub fn to_endnote_code(&self) -> Option<u32> {
+        ENDNOTE_CODES
+            .iter()
+            .find(|(reference_type, _)| reference_type == self)
+            .map(|(_, code)| *code)
+    }
+
+    /// Convert an `Other` type which is a standard abbreviation once trimmed, such as `Other("jour ")`,
+    /// to the corresponding variant. Abbreviations are matched case-insensitively, as when parsing.
+    ///
+    /// Other types are left as they are, including `Other` types which don't match any abbreviation,
+    /// which keep their original value.
+    pub fn canonicalize(&mut self) {
+        if let ReferenceType::Other(abbreviation) = self {
+            let canonical: ReferenceType = abbreviation.trim().parse().unwrap();
+
+            if !matches!(canonical, ReferenceType::Other(_)) {
+                *self = canonical;
+            }
+        }
+    }
+
+    /// The closest standard BibTeX entry type for this reference type, such as `article` or `inproceedings`.
+    ///
+    /// Types without a close equivalent, including `Other`, are mapped to `misc`.
+    pub fn bibtex_type(&self) -> &'static str {
+        use ReferenceType::*;
+
+        match self {
+            Journal | JournalFull | MagazineArticle | Newspaper | ElectronicArticle | InPress
+            | Abstract => "article",
+            WholeBook | EditedBook | ElectronicBook | ClassicalWork | AncientText | Dictionary
+            | Encyclopedia => "book",
+            BookChapter | ElectronicBookSection => "incollection",
+            ConferencePaper => "inproceedings",
+            ConferenceProceeding => "proceedings",
+            ThesisOrDissertation => "phdthesis",
+            Report | GovernmentDocument => "techreport",
+            Pamphlet => "booklet",
+            UnpublishedWork | Manuscript => "unpublished",
+            _ => "misc",
+        }
+    }
+
+    /// The earliest year in which a reference of this type could plausibly have been published,
+    /// used by [Entry::date_warnings](crate::Entry::date_warnings).
+    fn earliest_plausible_year(&self) -> Option<i32> {
+        use ReferenceType::*;
+
+        match self {
+            AncientText | ClassicalWork | Manuscript | ArtWork | Map | Other(_) => None,
+            WebPage
+            | Blog
+            | ElectronicArticle
+            | ElectronicBook
+            | ElectronicBookSection
+            | OnlineDatabase
+            | OnlineMultimedia
+            | AggregatedDatabase
+            | InternetCommunication
+            | DataFile
+            | ComputerProgram => Some(1950),
+            _ => Some(1450),
+        }
+    }
+}
+
+/// The mapping between reference types and EndNote numeric codes.
+const ENDNOTE_CODES: &[(ReferenceType, u32)] = &[
+    (ReferenceType::AncientText, 51),
+    (ReferenceType::ArtWork, 2),
+    (ReferenceType::AudiovisualMaterial, 3),
+    (ReferenceType::AggregatedDatabase, 55),
+    (ReferenceType::Bill, 4),
+    (ReferenceType::Blog, 56),
+    (ReferenceType::WholeBook, 6),
+    (ReferenceType::BookChapter, 5),
+    (ReferenceType::Case, 7),
+    (ReferenceType::Catalog, 8),
+    (ReferenceType::Chart, 38),
+    (ReferenceType::ClassicalWork, 49),
+    (ReferenceType::ComputerProgram, 9),
+    (ReferenceType::ConferencePaper, 47),
+    (ReferenceType::ConferenceProceeding, 10),
+    (ReferenceType::DataFile, 59),
+    (ReferenceType::Dictionary, 52),
+    (ReferenceType::EditedBook, 28),
+    (ReferenceType::ElectronicArticle, 43),
+    (ReferenceType::ElectronicBook, 44),
+    (ReferenceType::ElectronicBookSection, 60),
+    (ReferenceType::Encyclopedia, 53),
+    (ReferenceType::Equation, 39),
+    (ReferenceType::Figure, 37),
+    (ReferenceType::MotionPicture, 21),
+    (ReferenceType::Generic, 13),
+    (ReferenceType::GovernmentDocument, 46),
+    (ReferenceType::Grant, 54),
+    (ReferenceType::Hearing, 14),
+    (ReferenceType::Journal, 17),
+    (ReferenceType::LegalRuleOrRegulation, 50),
+    (ReferenceType::MagazineArticle, 19),
+    (ReferenceType::Manuscript, 36),
+    (ReferenceType::Map, 20),
+    (ReferenceType::MusicScore, 61),
+    (ReferenceType::Newspaper, 23),
+    (ReferenceType::OnlineDatabase, 45),
+    (ReferenceType::OnlineMultimedia, 48),
+    (ReferenceType::Pamphlet, 24),
+    (ReferenceType::Patent, 25),
+    (ReferenceType::PersonalCommunication, 26),
+    (ReferenceType::Report, 27),
+    (ReferenceType::SerialPublication, 57),
+    (ReferenceType::Standard, 58),
+    (ReferenceType::Statute, 31),
+    (ReferenceType::ThesisOrDissertation, 32),
+    (ReferenceType::UnpublishedWork, 34),
+    (ReferenceType::WebPage, 12),
+];
+
+impl FromStr for ReferenceType {
+    type Err = Infallible;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        use ReferenceType::*;
+
+        Ok(match s.to_ascii_uppercase().as_str() {
+            "ABST" => Abstract,
+            "ADVS" => AudiovisualMaterial,
+            "AGGR" => AggregatedDatabase,
+            "ANCIENT" => AncientText,
+            "ART" => ArtWork,
+            "BILL" => Bill,
+            "BLOG" => Blog,
+            "BOOK" => WholeBook,
+            "CASE" => Case,
+            "CHAP" => BookChapter,
+            "CHART" => Chart,
+            "CLSWK" => ClassicalWork,
+            "COMP" => ComputerProgram,
+            "CONF" => ConferenceProceeding,
+            "CPAPER" => ConferencePaper,
+            "CTLG" => Catalog,
+            "DATA" => DataFile,
+            "DBASE" => OnlineDatabase,
+            "DICT" => Dictionary,
+            "EBOOK" => ElectronicBook,
+            "ECHAP" => ElectronicBookSection,
+            "EDBOOK" => EditedBook,
+            "EJOUR" => ElectronicArticle,
+            "ELEC" => WebPage,
+            "ENCYC" => Encyclopedia,
+            "EQUA" => Equation,
+            "FIGURE" => Figure,
+            "GEN" => Generic,
+            "GOVDOC" => GovernmentDocument,
+            "GRANT" => Grant,
+            "HEAR" => Hearing,
+            "ICOMM" => InternetCommunication,
+            "INPR" => InPress,
+            "JFULL" => JournalFull,
+            "JOUR" => Journal,
+            "LEGAL" => LegalRuleOrRegulation,
+            "MANSCPT" => Manuscript,
+            "MAP" => Map,
+            "MGZN" => MagazineArticle,
+            "MPCT" => MotionPicture,
+            "MULTI" => OnlineMultimedia,
+            "MUSIC" => MusicScore,
+            "NEWS" => Newspaper,
+            "PAMP" => Pamphlet,
+            "PAT" => Patent,
+            "PCOMM" => PersonalCommunication,
+            "RPRT" => Report,
+            "SER" => SerialPublication,
+            "SLIDE" => Slide,
+            "SOUND" => SoundRecording,
+            "STAND" => Standard,
+            "STAT" => Statute,
+            "THES" => ThesisOrDissertation,
+            "UNPB" => UnpublishedWork,
+            "VIDEO" => VideoRecording,
+            _ => Other(s.to_owned()),
+        })
+    }
+}
+
+impl Display for ReferenceType {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        use ReferenceType::*;
+
+        let s = match self {
+            Abstract => "ABST",
+            AudiovisualMaterial => "ADVS",
+            AggregatedDatabase => "AGGR",
+            AncientText => "ANCIENT",
+            ArtWork => "ART",
+            Bill => "BILL",
+            Blog => "BLOG",
+            WholeBook => "BOOK",
+            Case => "CASE",
+            BookChapter => "CHAP",
+            Chart => "CHART",
+            ClassicalWork => "CLSWK",
+            ComputerProgram => "COMP",
+            ConferenceProceeding => "CONF",
+            ConferencePaper => "CPAPER",
+            Catalog => "CTLG",
+            DataFile => "DATA",
+            OnlineDatabase => "DBASE",
+            Dictionary => "DICT",
+            ElectronicBook => "EBOOK",
+            ElectronicBookSection => "ECHAP",
+            EditedBook => "EDBOOK",
+            ElectronicArticle => "EJOUR",
+            WebPage => "ELEC",
+            Encyclopedia => "ENCYC",
+            Equation => "EQUA",
+            Figure => "FIGURE",
+            Generic => "GEN",
+            GovernmentDocument => "GOVDOC",
+            Grant => "GRANT",
+            Hearing => "HEAR",
+            InternetCommunication => "ICOMM",
+            InPress => "INPR",
+            JournalFull => "JFULL",
+            Journal => "JOUR",
+            LegalRuleOrRegulation => "LEGAL",
+            Manuscript => "MANSCPT",
+            Map => "MAP",
+            MagazineArticle => "MGZN",
+            MotionPicture => "MPCT",
+            OnlineMultimedia => "MULTI",
+            MusicScore => "MUSIC",
+            Newspaper => "NEWS",
+            Pamphlet => "PAMP",
+            Patent => "PAT",
+            PersonalCommunication => "PCOMM",
+            Report => "RPRT",
+            SerialPublication => "SER",
+            Slide => "SLIDE",
+            SoundRecording => "SOUND",
+            Standard => "STAND",
+            Statute => "STAT",
+            ThesisOrDissertation => "THES",
+            UnpublishedWork => "UNPB",
+            VideoRecording => "VIDEO",
+            Other(s) => s,
+        };
+
+        f.write_str(s)
+    }
+}
+
+/// The (partial) date of publication of a reference.
+///
+/// The `year` field is mandatory, all the others are optional.
+///
+/// This type implements [Display](std::fmt::Display) and [FromStr](std::str::FromStr) to (de)serialize to/from strings.
+///
+/// Some examples of valid strings:
+/// - `1998///`
+/// - `1995/12/01/someotherinfo`
+/// - `1998/03//`
+/// - `1998///someotherinfo`
+/// - `2001`
+///
+/// Dates are only guaranteed to survive being serialized and parsed back if the year is between 0 and 9999,
+/// and the `other_info` is either `None` or non-empty: `Some("")` is parsed back as `None`.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct PublicationDate {
+    pub year: i32,
+    pub month: Option<i32>,
+    pub day: Option<i32>,
+    pub other_info: Option<String>,
+}
+
+impl PublicationDate {
+    pub fn new(
+        year: i32,
+        month: Option<i32>,
+        day: Option<i32>,
+        other_info: Option<String>,
+    ) -> Self {
+        Self {
+            year,
+            month,
+            day,
+            other_info,
+        }
+    }
+
+    /// Whether the month, if present, is between 1 and 12, and the day, if present, exists in that month.
+    ///
+    /// A day without a month is valid if it is between 1 and 31.
+    pub fn is_valid(&self) -> bool {
+        let days_in_month = match self.month {
+            None => 31,
+            Some(month) => match days_in_month(self.year, month) {
+                Some(days) => days,
+                None => return false,
+            },
+        };
+
+        self.day
+            .map_or(true, |day| (1..=days_in_month).contains(&day))
+    }
+}
+
+/// The number of days in a month, or `None` if the month is not between 1 and 12.
+fn days_in_month(year: i32, month: i32) -> Option<i32> {
+    match month {
+        1 | 3 | 5 | 7 | 8 | 10 | 12 => Some(31),
+        4 | 6 | 9 | 11 => Some(30),
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => Some(29),
+        2 => Some(28),
+        _ => None,
+    }
+}
+
+/// An error occurring during the parsing of a publication date
+pub struct ParseDateError;
+
+impl FromStr for PublicationDate {
+    type Err = ParseDateError;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        lazy_static! {
+            static ref DATE_RE: Regex =
+                Regex::new("(\\d\\d\\d\\d)(?:/(\\d\\d?)?(?:/(\\d\\d?)?(?:/(.+)?)?)?)?").unwrap();
+        }
+
+        let matches = DATE_RE.captures(s).ok_or(ParseDateError)?;
+
+        let year = matches
+            .get(1)
+            .unwrap()
+            .as_str()
+            .parse()
+            .map_err(|_| ParseDateError)?;
+
+        let month = matches
+            .get(2)
+            .map(|m| m.as_str().parse())
+            .transpose()
+            .map_err(|_| ParseDateError)?;
+
+        let day = matches
+            .get(3)
+            .map(|d| d.as_str().parse())
+            .transpose()
+            .map_err(|_| ParseDateError)?;
+
+        let other_info = matches.get(4).map(|s| s.as_str().to_owned());
+
+        Ok(Self::new(year, month, day, other_info))
+    }
+}
+
+impl Display for PublicationDate {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        self.write(f, true)
+    }
+}
+
+impl PublicationDate {
+    /// Write the date in the `YYYY/MM/DD/other info` format, optionally without leading zeros on the month and day.
+    fn write<W: fmt::Write>(&self, f: &mut W, padded: bool) -> fmt::Result {
+        write!(f, "{:04}/", self.year)?;
+
+        if let Some(month) = self.month {
+            if padded {
+                write!(f, "{:02}", month)?;
+            } else {
+                write!(f, "{}", month)?;
+            }
+        }
+
+        write!(f, "/")?;
+
+        if let Some(day) = self.day {
+            if padded {
+                write!(f, "{:02}", day)?;
+            } else {
+                write!(f, "{}", day)?;
+            }
+        }
+
+        write!(f, "/")?;
+
+        if let Some(ref other_info) = self.other_info {
+            write!(f, "{}", other_info)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// A warning about an implausible year in a date of an [Entry](crate::Entry).
+///
+/// See [Entry::date_warnings](crate::Entry::date_warnings) for the thresholds used.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DateWarning {
+    /// The year is after the next calendar year.
+    FutureYear { field: &'static str, year: i32 },
+    /// The year is earlier than plausible for the reference type of the entry.
+    ImplausiblyOld { field: &'static str, year: i32 },
+}
+
+/// The current year according to the system clock, in UTC.
+fn current_year() -> i32 {
+    let days = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs() / 86400) as i64;
+
+    // Days to civil date, from http://howardhinnant.github.io/date_algorithms.html
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    year as i32
+}
+
+/// The name of an author, in the `Lastname, Firstnames, Suffix` format used by RIS.
+///
+/// The first names and suffix are optional, and a trailing parenthesized ORCID
+/// (e.g. `Shannon, Claude E. (0000-0001-2345-6789)`) is extracted into its own field.
+///
+/// This type implements [Display](std::fmt::Display) and [FromStr](std::str::FromStr) to (de)serialize to/from strings.
+/// Since author fields in an [Entry](crate::Entry) are stored as raw strings, see [Entry::parsed_authors](crate::Entry::parsed_authors)
+/// to parse them.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Author {
+    pub last_name: String,
+    pub first_names: Option<String>,
+    pub suffix: Option<String>,
+    pub orcid: Option<String>,
+}
+
+impl FromStr for Author {
+    type Err = Infallible;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        lazy_static! {
+            static ref ORCID_RE: Regex =
+                Regex::new("^(.*?)\\s*\\((\\d{4}-\\d{4}-\\d{4}-\\d{3}[\\dX])\\)\\s*$").unwrap();
+        }
+
+        let (name, orcid) = match ORCID_RE.captures(s) {
+            Some(matches) => (
+                matches.get(1).unwrap().as_str(),
+                Some(matches.get(2).unwrap().as_str().to_owned()),
+            ),
+            None => (s, None),
+        };
+
+        let mut parts = name.splitn(3, ',').map(str::trim);
+        let mut next_part = || parts.next().filter(|p| !p.is_empty()).map(str::to_owned);
+
+        Ok(Self {
+            last_name: next_part().unwrap_or_default(),
+            first_names: next_part(),
+            suffix: next_part(),
+            orcid,
+        })
+    }
+}
+
+impl Display for Author {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.write_str(&self.last_name)?;
+
+        if let Some(ref first_names) = self.first_names {
+            write!(f, ", {}", first_names)?;
+        }
+
+        if let Some(ref suffix) = self.suffix {
+            write!(f, ", {}", suffix)?;
+        }
+
+        if let Some(ref orcid) = self.orcid {
+            write!(f, " ({})", orcid)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// An error occurring during the parsing of a RIS file.
+#[derive(Debug, Clone)]
+pub struct ParseError {
+    /// The line number (starting at 1) on which the error occurred.
+    pub line_no: usize,
+    /// The kind of error
+    pub kind: ParseErrorKind,
+}
+
+impl ParseError {
+    pub fn new(line_no: usize, kind: ParseErrorKind) -> Self {
+        Self { line_no, kind }
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match &self.kind {
+            ParseErrorKind::TagOutsideEntry => write!(f, "Tag outside entry"),
+            ParseErrorKind::UnterminatedEntry => write!(f, "Unterminated entry"),
+            ParseErrorKind::InvalidKey(key) => write!(f, "Invalid key `{}`", key),
+            ParseErrorKind::InvalidLine => write!(f, "Invalid line format"),
+            ParseErrorKind::DuplicateField => write!(f, "Duplicate field"),
+            ParseErrorKind::InvalidDate => write!(f, "Invalid date format"),
+        }?;
+
+        write!(f, " at line {}", self.line_no)?;
+
+        Ok(())
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// An error occurring while reading a RIS file from a reader.
+#[derive(Debug)]
+pub enum ReadError {
+    /// The reader failed.
+    Io(io::Error),
+    /// The RIS file was invalid.
+    Parse(ParseError),
+}
+
+impl From<io::Error> for ReadError {
+    fn from(e: io::Error) -> Self {
+        ReadError::Io(e)
+    }
+}
+
+impl From<ParseError> for ReadError {
+    fn from(e: ParseError) -> Self {
+        ReadError::Parse(e)
+    }
+}
+
+impl Display for ReadError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            ReadError::Io(e) => write!(f, "IO error: {}", e),
+            ReadError::Parse(e) => e.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for ReadError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ReadError::Io(e) => Some(e),
+            ReadError::Parse(e) => Some(e),
+        }
+    }
+}
+
+/// The kind of an error occurring during the parsing of a RIS file.
+#[derive(Debug, Clone)]
+pub enum ParseErrorKind {
+    /// A tag other than `TY` was present outside of an entry.
+    TagOutsideEntry,
+    /// An entry was not terminated by an `ER` tag.
+    UnterminatedEntry,
+    /// An unrecognized key was encountered. Contains the key.
+    InvalidKey(String),
+    /// A line was not in the RIS format of `<letter><letter_or_number><space><space><dash><space><any>*`.
+    InvalidLine,
+    /// A unique field was present multiple times in a single entry.
+    DuplicateField,
+    /// A date field was not in the `YYYY/MM/DD/otherinfo` format.
+    InvalidDate,
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn deserialize_one_record() {
+        let s = "TY  - JOUR
+AU  - Shannon, Claude E.
+PY  - 1948/07//
+TI  - A Mathematical Theory of Communication
+T2  - Bell System Technical Journal
+SP  - 379
+EP  - 423
+VL  - 27
+ER  - ";
+
+        let ris = RIS(vec![Entry {
+            authors: vec![String::from("Shannon, Claude E.")],
+            primary_date: Some(PublicationDate::new(1948, Some(7), None, None)),
+            title: Some(String::from("A Mathematical Theory of Communication")),
+            secondary_title: Some(String::from("Bell System Technical Journal")),
+            start_page: Some(String::from("379")),
+            end_page: Some(String::from("423")),
+            volume: Some(String::from("27")),
+            ..Entry::new(ReferenceType::Journal)
+        }]);
+
+        assert_eq!(ris, RIS::from_str(s).unwrap());
+    }
+
+    #[test]
+    fn deserialize_two_records() {
+        let s = "TY  - JOUR
+AU  - Shannon, Claude E.
+PY  - 1948/07//
+TI  - A Mathematical Theory of Communication
+T2  - Bell System Technical Journal
+SP  - 379
+EP  - 423
+VL  - 27
+ER  - \nTY  - JOUR
+T1  - On computable numbers, with an application to the Entscheidungsproblem
+A1  - Turing, Alan Mathison
+JO  - Proc. of London Mathematical Society
+VL  - 47
+IS  - 1
+SP  - 230
+EP  - 265
+Y1  - 1937
+ER  - ";
+
+        let ris = RIS(vec![
+            Entry {
+                authors: vec![String::from("Shannon, Claude E.")],
+                primary_date: Some(PublicationDate::new(1948, Some(7), None, None)),
+                title: Some(String::from("A Mathematical Theory of Communication")),
+                secondary_title: Some(String::from("Bell System Technical Journal")),
+                start_page: Some(String::from("379")),
+                end_page: Some(String::from("423")),
+                volume: Some(String::from("27")),
+                ..Entry::new(ReferenceType::Journal)
+            },
+            Entry {
+                title: Some(String::from(
+                    "On computable numbers, with an application to the Entscheidungsproblem",
+                )),
+                authors: vec![String::from("Turing, Alan Mathison")],
+                journal: Some(String::from("Proc. of London Mathematical Society")),
+                volume: Some(String::from("47")),
+                issue: Some(String::from("1")),
+                start_page: Some(String::from("230")),
+                end_page: Some(String::from("265")),
+                primary_date: Some(PublicationDate::new(1937, None, None, None)),
+                ..Entry::new(ReferenceType::Journal)
+            },
+        ]);
+
+        assert_eq!(ris, RIS::from_str(s).unwrap());
+    }
+
+    #[test]
+    fn serialize_one_record() {
+        let ris = RIS(vec![Entry {
+            authors: vec![String::from("Shannon, Claude E.")],
+            primary_date: Some(PublicationDate::new(1948, Some(7), None, None)),
+            title: Some(String::from("A Mathematical Theory of Communication")),
+            secondary_title: Some(String::from("Bell System Technical Journal")),
+            start_page: Some(String::from("379")),
+            end_page: Some(String::from("423")),
+            volume: Some(String::from("27")),
+            ..Entry::new(ReferenceType::Journal)
+        }]);
+
+        let s = "TY  - JOUR
+T1  - A Mathematical Theory of Communication
+T2  - Bell System Technical Journal
+A1  - Shannon, Claude E.
+Y1  - 1948/07//
+SP  - 379
+EP  - 423
+VL  - 27
+ER  - ";
+
+        assert_eq!(ris.to_string(), s);
+    }
+
+    #[test]
+    fn serialize_two_records() {
+        let ris = RIS(vec![
+            Entry {
+                authors: vec![String::from("Shannon, Claude E.")],
+                primary_date: Some(PublicationDate::new(1948, Some(7), None, None)),
+                title: Some(String::from("A Mathematical Theory of Communication")),
+                secondary_title: Some(String::from("Bell System Technical Journal")),
+                start_page: Some(String::from("379")),
+                end_page: Some(String::from("423")),
+                volume: Some(String::from("27")),
+                ..Entry::new(ReferenceType::Journal)
+            },
+            Entry {
+                title: Some(String::from(
+                    "On computable numbers, with an application to the Entscheidungsproblem",
+                )),
+                authors: vec![String::from("Turing, Alan Mathison")],
+                journal: Some(String::from("Proc. of London Mathematical Society")),
+                volume: Some(String::from("47")),
+                issue: Some(String::from("1")),
+                start_page: Some(String::from("230")),
+                end_page: Some(String::from("265")),
+                primary_date: Some(PublicationDate::new(1937, None, None, None)),
+                ..Entry::new(ReferenceType::Journal)
+            },
+        ]);
+
+        let s = "TY  - JOUR
+T1  - A Mathematical Theory of Communication
+T2  - Bell System Technical Journal
+A1  - Shannon, Claude E.
+Y1  - 1948/07//
+SP  - 379
+EP  - 423
+VL  - 27
+ER  - \nTY  - JOUR
+T1  - On computable numbers, with an application to the Entscheidungsproblem
+A1  - Turing, Alan Mathison
+Y1  - 1937///
+SP  - 230
+EP  - 265
+JF  - Proc. of London Mathematical Society
+VL  - 47
+IS  - 1
+ER  - ";
+
+        assert_eq!(ris.to_string(), s);
+    }
+
+    #[test]
+    fn assign_missing_ids() {
+        let mut ris = RIS(vec![
+            Entry::new(ReferenceType::Journal),
+            Entry {
+                id: Some(String::from("existing")),
+                ..Entry::new(ReferenceType::Journal)
+            },
+            Entry::new(ReferenceType::WholeBook),
+        ]);
+
+        ris.assign_ids(|_, i| format!("ref{}", i + 1));
+
+        let ids: Vec<_> = ris.0.iter().map(|e| e.id.as_deref()).collect();
+        assert_eq!(ids, vec![Some("ref1"), Some("existing"), Some("ref3")]);
+    }
+
+    #[test]
+    fn invalid_key_names_the_key() {
+        let s = "TY  - JOUR
+XY  - Something
+ER  - ";
+
+        let err = RIS::from_str(s).unwrap_err();
+
+        match err.kind {
+            ParseErrorKind::InvalidKey(ref key) => assert_eq!(key, "XY"),
+            ref kind => panic!("unexpected error kind {:?}", kind),
+        }
+        assert_eq!(err.to_string(), "Invalid key `XY` at line 2");
+    }
+
+    #[test]
+    fn times_cited_from_user_2() {
+        let mut entry = Entry {
+            user_2: Some(String::from("42")),
+            ..Entry::new(ReferenceType::Journal)
+        };
+        assert_eq!(entry.times_cited(), Some(42));
+
+        entry.user_2 = Some(String::from("not a number"));
+        assert_eq!(entry.times_cited(), None);
+
+        entry.user_2 = None;
+        assert_eq!(entry.times_cited(), None);
+    }
+
+    #[test]
+    fn truncate_dates_to_year() {
+        let mut ris = RIS(vec![Entry {
+            primary_date: Some(PublicationDate::new(
+                1995,
+                Some(12),
+                Some(1),
+                Some(String::from("someotherinfo")),
+            )),
+            secondary_date: Some(PublicationDate::new(1996, Some(3), None, None)),
+            ..Entry::new(ReferenceType::Journal)
+        }]);
+
+        ris.truncate_dates_to_year();
+
+        assert_eq!(
+            ris.0[0].primary_date,
+            Some(PublicationDate::new(1995, None, None, None))
+        );
+        assert_eq!(
+            ris.to_string(),
+            "TY  - JOUR\nY1  - 1995///\nY2  - 1996///\nER  - "
+        );
+    }
+
+    #[test]
+    fn multiline_hyphenated_values() {
+        let s = "TY  - JOUR
+AB  - A very long abstract with a hyphen-
+ated word, wrapped over
+several lines
+ER  - ";
+
+        let joined = RIS::from_str_with_options(
+            s,
+            &ParseOptions {
+                multiline_values: true,
+                join_hyphenated_lines: true,
+                ..ParseOptions::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            joined.0[0].abstract_.as_deref(),
+            Some("A very long abstract with a hyphenated word, wrapped over\nseveral lines")
+        );
+
+        let preserved = RIS::from_str_with_options(
+            s,
+            &ParseOptions {
+                multiline_values: true,
+                ..ParseOptions::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            preserved.0[0].abstract_.as_deref(),
+            Some("A very long abstract with a hyphen-\nated word, wrapped over\nseveral lines")
+        );
+
+        assert!(RIS::from_str(s).is_err());
+    }
+
+    #[test]
+    fn change_reference_type() {
+        let entry: Entry = "TY  - JOUR
+BT  - Some Book
+ER  - "
+            .parse()
+            .unwrap();
+
+        let entry = entry.with_reference_type(ReferenceType::WholeBook);
+
+        assert_eq!(entry.reference_type, ReferenceType::WholeBook);
+        assert_eq!(entry.title, None);
+        assert_eq!(entry.secondary_title.as_deref(), Some("Some Book"));
+    }
+
+    #[test]
+    fn extract_from_email() {
+        let s = "From: library@example.com
+Subject: Your export
+Content-Type: multipart/mixed; boundary=\"XYZ\"
+
+--XYZ
+TY  - JOUR
+AU  - Shannon, Claude E.
+PY  - 1948/07//
+ER  - 
+--XYZ--
+";
+
+        let ris = RIS::from_str_extract(s).unwrap();
+
+        assert_eq!(
+            ris,
+            RIS(vec![Entry {
+                authors: vec![String::from("Shannon, Claude E.")],
+                primary_date: Some(PublicationDate::new(1948, Some(7), None, None)),
+                ..Entry::new(ReferenceType::Journal)
+            }])
+        );
+
+        let err = RIS::from_str_extract("Preamble\nTY  - JOUR\nbroken\nER  - ").unwrap_err();
+        assert_eq!(err.line_no, 3);
+    }
+
+    #[test]
+    fn dominant_type() {
+        let mut ris = RIS(vec![
+            Entry::new(ReferenceType::WholeBook),
+            Entry::new(ReferenceType::Journal),
+            Entry::new(ReferenceType::Journal),
+            Entry::new(ReferenceType::WholeBook),
+            Entry::new(ReferenceType::Journal),
+        ]);
+
+        assert_eq!(ris.count_by_type()[&ReferenceType::Journal], 3);
+        assert_eq!(ris.dominant_type(), Some(ReferenceType::Journal));
+
+        ris.0.pop();
+        assert_eq!(ris.dominant_type(), Some(ReferenceType::WholeBook));
+
+        assert_eq!(RIS(Vec::new()).dominant_type(), None);
+    }
+
+    #[test]
+    fn count_fields_with_visitor() {
+        #[derive(Default)]
+        struct Counter {
+            types: Vec<String>,
+            fields: usize,
+            ended: usize,
+        }
+
+        impl RisVisitor for Counter {
+            fn on_entry_start(&mut self, reference_type: &str) {
+                self.types.push(String::from(reference_type));
+            }
+
+            fn on_field(&mut self, _tag: &str, _value: &str) {
+                self.fields += 1;
+            }
+
+            fn on_entry_end(&mut self) {
+                self.ended += 1;
+            }
+        }
+
+        let s = "TY  - JOUR
+AU  - Shannon, Claude E.
+PY  - 1948/07//
+TI  - A Mathematical Theory of Communication
+ER  - 
+TY  - BOOK
+XY  - Unknown tags are reported too
+ER  - ";
+
+        let mut counter = Counter::default();
+        parse_with_visitor(s, &mut counter).unwrap();
+
+        assert_eq!(counter.types, vec!["JOUR", "BOOK"]);
+        assert_eq!(counter.fields, 4);
+        assert_eq!(counter.ended, 2);
+
+        assert!(parse_with_visitor("TY  - JOUR\nAU  - Nobody", &mut Counter::default()).is_err());
+    }
+
+    #[test]
+    fn parse_authors_with_orcid() {
+        let entry: Entry = "TY  - JOUR
+AU  - Shannon, Claude E. (0000-0001-2345-6789)
+AU  - Turing, Alan Mathison
+ER  - "
+            .parse()
+            .unwrap();
+
+        assert_eq!(
+            entry.parsed_authors(),
+            vec![
+                Author {
+                    last_name: String::from("Shannon"),
+                    first_names: Some(String::from("Claude E.")),
+                    suffix: None,
+                    orcid: Some(String::from("0000-0001-2345-6789")),
+                },
+                Author {
+                    last_name: String::from("Turing"),
+                    first_names: Some(String::from("Alan Mathison")),
+                    suffix: None,
+                    orcid: None,
+                },
+            ]
+        );
+        assert_eq!(entry.authors[0], "Shannon, Claude E. (0000-0001-2345-6789)");
+        assert_eq!(entry.parsed_authors()[0].to_string(), entry.authors[0]);
+    }
+
+    #[test]
+    fn validate_doi() {
+        let mut entry = Entry::new(ReferenceType::Journal);
+        assert_eq!(entry.doi_is_valid(), None);
+
+        entry.doi = Some(String::from("10.1002/j.1538-7305.1948.tb01338.x"));
+        assert_eq!(entry.doi_is_valid(), Some(true));
+
+        entry.doi = Some(String::from(
+            "https://doi.org/10.1002/j.1538-7305.1948.tb01338.x",
+        ));
+        assert_eq!(entry.doi_is_valid(), Some(true));
+        assert_eq!(
+            entry.normalized_doi(),
+            Some("10.1002/j.1538-7305.1948.tb01338.x")
+        );
+
+        entry.doi = Some(String::from("doi:10.1112/plms/s2-42.1.230"));
+        assert_eq!(entry.doi_is_valid(), Some(true));
+
+        entry.doi = Some(String::from("not a doi"));
+        assert_eq!(entry.doi_is_valid(), Some(false));
+
+        entry.doi = Some(String::from("10.12/too-short-registrant"));
+        assert_eq!(entry.doi_is_valid(), Some(false));
+    }
+
+    #[test]
+    fn pretty_string() {
+        let ris = RIS(vec![
+            Entry {
+                title: Some(String::from("A Mathematical Theory of Communication")),
+                ..Entry::new(ReferenceType::Journal)
+            },
+            Entry::new(ReferenceType::Other(String::from("WEIRD"))),
+        ]);
+
+        let s = "# Entry 1: Journal
+TY  - JOUR
+T1  - A Mathematical Theory of Communication
+ER  - 
+
+# Entry 2: WEIRD
+TY  - WEIRD
+ER  - 
+";
+
+        assert_eq!(ris.to_pretty_string(), s);
+    }
+
+    #[test]
+    fn build_entry_with_macro() {
+        let entry = ris_entry! {
+            type: Journal,
+            title: "On computable numbers, with an application to the Entscheidungsproblem",
+            authors: vec!["Turing, Alan Mathison"],
+            keywords: ["computability", "decision problem"],
+            year: 1937,
+            journal: String::from("Proc. of London Mathematical Society"),
+        };
+
+        assert_eq!(
+            entry,
+            Entry {
+                title: Some(String::from(
+                    "On computable numbers, with an application to the Entscheidungsproblem",
+                )),
+                authors: vec![String::from("Turing, Alan Mathison")],
+                keywords: vec![
+                    String::from("computability"),
+                    String::from("decision problem")
+                ],
+                primary_date: Some(PublicationDate::new(1937, None, None, None)),
+                journal: Some(String::from("Proc. of London Mathematical Society")),
+                ..Entry::new(ReferenceType::Journal)
+            }
+        );
+    }
+
+    #[test]
+    fn apply_lines() {
+        let mut entry = Entry::new(ReferenceType::Generic);
+
+        entry.apply_line("TY  - JOUR").unwrap();
+        entry.apply_line("AU  - Shannon, Claude E.").unwrap();
+        entry.apply_line("PY  - 1948/07//").unwrap();
+        entry.apply_line("ER  - ").unwrap();
+
+        assert_eq!(
+            entry,
+            Entry {
+                authors: vec![String::from("Shannon, Claude E.")],
+                primary_date: Some(PublicationDate::new(1948, Some(7), None, None)),
+                ..Entry::new(ReferenceType::Journal)
+            }
+        );
+
+        assert!(matches!(
+            entry.apply_line("Y1  - 1948").unwrap_err().kind,
+            ParseErrorKind::DuplicateField
+        ));
+        assert!(matches!(
+            entry.apply_line("not a line").unwrap_err().kind,
+            ParseErrorKind::InvalidLine
+        ));
+    }
+
+    #[test]
+    fn parse_limited() {
+        let s = "TY  - JOUR
+ER  - 
+TY  - BOOK
+ER  - 
+TY  - CHAP
+ER  - 
+this line is never reached";
+
+        let ris = RIS::from_str_limited(s, 2).unwrap();
+        assert_eq!(
+            ris,
+            RIS(vec![
+                Entry::new(ReferenceType::Journal),
+                Entry::new(ReferenceType::WholeBook)
+            ])
+        );
+
+        assert_eq!(RIS::from_str_limited(s, 0).unwrap(), RIS(Vec::new()));
+        assert!(RIS::from_str_limited(s, 4).is_err());
+    }
+
+    #[test]
+    fn read_from_reader() {
+        let s = "TY  - JOUR
+AU  - Shannon, Claude E.
+ER  - 
+TY  - BOOK
+ER  - ";
+
+        let ris = RIS::from_reader(s.as_bytes()).unwrap();
+        assert_eq!(ris, RIS::from_str(s).unwrap());
+
+        let mut entries = EntryReader::new("TY  - JOUR\nER  - \nTY  - BOOK".as_bytes());
+        assert_eq!(
+            entries.next().unwrap().unwrap(),
+            Entry::new(ReferenceType::Journal)
+        );
+        assert!(matches!(
+            entries.next(),
+            Some(Err(ReadError::Parse(ParseError {
+                line_no: 3,
+                kind: ParseErrorKind::UnterminatedEntry
+            })))
+        ));
+        assert!(entries.next().is_none());
+    }
+
+    #[test]
+    fn normalized_title() {
+        let a = Entry {
+            title: Some(String::from("A Mathematical Theory of Communication.")),
+            ..Entry::new(ReferenceType::Journal)
+        };
+        let b = Entry {
+            title: Some(String::from("  a mathematical theory of  communication ")),
+            ..Entry::new(ReferenceType::Journal)
+        };
+
+        assert_eq!(
+            a.normalized_title().as_deref(),
+            Some("a mathematical theory of communication")
+        );
+        assert_eq!(a.normalized_title(), b.normalized_title());
+        assert_eq!(Entry::new(ReferenceType::Journal).normalized_title(), None);
+    }
+
+    #[test]
+    fn primary_date_conflicts() {
+        let compatible = "TY  - JOUR
+PY  - 1948
+Y1  - 1948/07//
+ER  - ";
+        let conflicting = "TY  - JOUR
+PY  - 1948
+Y1  - 1949/07//
+ER  - ";
+
+        let with_policy = |s, policy| {
+            RIS::from_str_with_options(
+                s,
+                &ParseOptions {
+                    primary_date_conflicts: policy,
+                    ..ParseOptions::default()
+                },
+            )
+            .map(|ris| ris.0[0].primary_date.clone().unwrap())
+        };
+
+        assert!(matches!(
+            RIS::from_str(compatible).unwrap_err().kind,
+            ParseErrorKind::DuplicateField
+        ));
+
+        assert_eq!(
+            with_policy(compatible, DateConflictPolicy::Merge).unwrap(),
+            PublicationDate::new(1948, Some(7), None, None)
+        );
+        assert!(with_policy(conflicting, DateConflictPolicy::Merge).is_err());
+
+        assert_eq!(
+            with_policy(compatible, DateConflictPolicy::KeepFirst).unwrap(),
+            PublicationDate::new(1948, None, None, None)
+        );
+        assert_eq!(
+            with_policy(conflicting, DateConflictPolicy::KeepFirst).unwrap(),
+            PublicationDate::new(1948, None, None, None)
+        );
+    }
+
+    #[test]
+    fn incomplete_entries() {
+        let ris = RIS(vec![
+            ris_entry! {
+                type: Journal,
+                title: "A Mathematical Theory of Communication",
+                authors: ["Shannon, Claude E."],
+                year: 1948,
+                secondary_title: "Bell System Technical Journal",
+            },
+            ris_entry! {
+                type: WholeBook,
+                title: "The Art of Computer Programming",
+                year: 1968,
+            },
+            ris_entry! { type: Generic },
+        ]);
+
+        assert_eq!(
+            ris.incomplete_entries(),
+            vec![(1, vec!["authors", "publisher"]), (2, vec!["title"])]
+        );
+    }
+
+    #[test]
+    fn stray_er_is_outside_entry() {
+        let err = RIS::from_str("ER  - ").unwrap_err();
+        assert!(matches!(err.kind, ParseErrorKind::TagOutsideEntry));
+        assert_eq!(err.line_no, 1);
+
+        let err = RIS::from_str("TY  - JOUR\nER  - \nER  - ").unwrap_err();
+        assert!(matches!(err.kind, ParseErrorKind::TagOutsideEntry));
+        assert_eq!(err.line_no, 3);
+    }
+
+    #[test]
+    fn unique_authors() {
+        let ris = RIS(vec![
+            ris_entry! {
+                type: Journal,
+                authors: ["Shannon, Claude E.", "Weaver, Warren"],
+            },
+            ris_entry! {
+                type: Journal,
+                authors: ["SHANNON, CLAUDE E."],
+            },
+            ris_entry! {
+                type: Journal,
+                authors: ["Turing, Alan Mathison"],
+            },
+        ]);
+
+        assert_eq!(
+            ris.unique_authors(),
+            vec![
+                "Shannon, Claude E.",
+                "Weaver, Warren",
+                "Turing, Alan Mathison"
+            ]
+        );
+        assert_eq!(
+            ris.author_counts(),
+            vec![
+                ("Shannon, Claude E.", 2),
+                ("Weaver, Warren", 1),
+                ("Turing, Alan Mathison", 1)
+            ]
+        );
+    }
+
+    #[test]
+    fn export_escaping() {
+        let ris = RIS(vec![ris_entry! {
+            type: Journal,
+            title: "Commas, \"quotes\"\nand newlines",
+            authors: ["Shannon, Claude E.", "Back\\slash"],
+        }]);
+
+        assert_eq!(
+            ris.to_json(),
+            r#"[{"reference_type":"JOUR","title":"Commas, \"quotes\"\nand newlines","authors":["Shannon, Claude E.","Back\\slash"]}]"#
+        );
+
+        let csv = ris.to_csv();
+        let (header, row) = csv.split_at(csv.find('\n').unwrap() + 1);
+
+        assert!(header.starts_with(
+            "reference_type,id,title,secondary_title,tertiary_title,short_title,authors,"
+        ));
+        let empty_columns = header.matches(',').count() - 6;
+        assert_eq!(
+            row,
+            format!(
+                "JOUR,,\"Commas, \"\"quotes\"\"\nand newlines\",,,,\"Shannon, Claude E.; Back\\slash\"{}\n",
+                ",".repeat(empty_columns)
+            )
+        );
+    }
+
+    #[test]
+    fn sort_authors() {
+        let mut ris = RIS(vec![ris_entry! {
+            type: Journal,
+            authors: ["Weaver, Warren", "shannon, Claude E.", "Turing, Alan Mathison"],
+        }]);
+
+        ris.sort_all_authors();
+
+        assert_eq!(
+            ris.0[0].authors,
+            vec![
+                "shannon, Claude E.",
+                "Turing, Alan Mathison",
+                "Weaver, Warren"
+            ]
+        );
+    }
+
+    #[test]
+    fn all_dois() {
+        let mut entry = Entry {
+            doi: Some(String::from(
+                "10.1002/j.1538-7305.1948.tb01338.x; https://doi.org/10.1112/plms/s2-42.1.230",
+            )),
+            ..Entry::new(ReferenceType::Journal)
+        };
+
+        assert_eq!(
+            entry.all_dois(),
+            vec![
+                "10.1002/j.1538-7305.1948.tb01338.x",
+                "10.1112/plms/s2-42.1.230"
+            ]
+        );
+
+        entry.doi = Some(String::from("10.1002/a doi: 10.1002/b"));
+        assert_eq!(entry.all_dois(), vec!["10.1002/a", "10.1002/b"]);
+
+        entry.doi = None;
+        assert!(entry.all_dois().is_empty());
+    }
+
+    #[test]
+    fn preserve_unknown_tags() {
+        let s = "TY  - JOUR
+XY  - Nonstandard
+T1  - Title
+ER  - ";
+
+        let options = ParseOptions {
+            preserve_unknown_tags: true,
+            ..ParseOptions::default()
+        };
+
+        let ris = RIS::from_str_with_options(s, &options).unwrap();
+        assert_eq!(
+            ris.0[0].unknown_tags,
+            vec![(String::from("XY"), String::from("Nonstandard"))]
+        );
+        assert_eq!(
+            ris.to_string(),
+            "TY  - JOUR\nT1  - Title\nXY  - Nonstandard\nER  - "
+        );
+
+        assert!(matches!(
+            RIS::from_str(s).unwrap_err().kind,
+            ParseErrorKind::InvalidKey(_)
+        ));
+
+        for line in &["xy  - lowercase", "XYZ  - too long", "junk XY  - prefixed"] {
+            let err =
+                RIS::from_str_with_options(&format!("TY  - JOUR\n{}\nER  - ", line), &options)
+                    .unwrap_err();
+            assert!(matches!(err.kind, ParseErrorKind::InvalidLine));
+        }
+    }
+
+    #[test]
+    fn parse_borrowed_entries() {
+        let s = "TY  - JOUR
+AU  - Shannon, Claude E.
+PY  - 1948/07//
+TI  - A Mathematical Theory of Communication
+T2  - Bell System Technical Journal
+SP  - 379
+EP  - 423
+VL  - 27
+ER  - 
+TY  - BOOK
+BT  - Some Book
+ER  - ";
+
+        let entries = parse_borrowed(s).unwrap();
+
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].authors, vec!["Shannon, Claude E."]);
+        assert_eq!(entries[0].primary_date, Some("1948/07//"));
+        assert_eq!(entries[1].title, Some("Some Book"));
+
+        let owned: Vec<Entry> = entries.iter().map(EntryRef::to_owned).collect();
+        assert_eq!(RIS(owned), RIS::from_str(s).unwrap());
+
+        assert!(matches!(
+            parse_borrowed("TY  - JOUR\nPY  - nope\nER  - ")
+                .unwrap_err()
+                .kind,
+            ParseErrorKind::InvalidDate
+        ));
+        assert!(matches!(
+            parse_borrowed("TY  - JOUR\nVL  - 1\nVL  - 2\nER  - ")
+                .unwrap_err()
+                .kind,
+            ParseErrorKind::DuplicateField
+        ));
+    }
+
+    #[test]
+    fn to_string_sorted_by() {
+        let ris = RIS(vec![
+            ris_entry! { type: Journal, title: "B" },
+            ris_entry! { type: Journal, title: "A" },
+        ]);
+
+        assert_eq!(
+            ris.to_string_sorted_by(|a, b| a.title.cmp(&b.title)),
+            "TY  - JOUR\nT1  - A\nER  - \nTY  - JOUR\nT1  - B\nER  - "
+        );
+        assert_eq!(ris.0[0].title.as_deref(), Some("B"));
+    }
+
+    #[test]
+    fn classification_and_workplace_round_trip() {
+        let s = "TY  - RPRT
+T1  - Some Report
+CL  - QA76.9
+WP  - Bell Labs
+ER  - ";
+
+        let entry: Entry = s.parse().unwrap();
+        assert_eq!(entry.classification.as_deref(), Some("QA76.9"));
+        assert_eq!(entry.workplace.as_deref(), Some("Bell Labs"));
+        assert_eq!(entry.to_string(), s);
+        assert_eq!(parse_borrowed(s).unwrap()[0].to_owned(), entry);
+
+        assert!(matches!(
+            RIS::from_str("TY  - RPRT\nCL  - A\nCL  - B\nER  - ")
+                .unwrap_err()
+                .kind,
+            ParseErrorKind::DuplicateField
+        ));
+    }
+
+    #[test]
+    fn completeness() {
+        let sparse = ris_entry! {
+            type: Journal,
+            title: "A Mathematical Theory of Communication",
+            volume: "27",
+        };
+        let rich = ris_entry! {
+            type: Journal,
+            title: "A Mathematical Theory of Communication",
+            authors: ["Shannon, Claude E."],
+            year: 1948,
+            journal: "Bell System Technical Journal",
+        };
+
+        assert_eq!(sparse.field_count(), 2);
+        assert_eq!(rich.field_count(), 4);
+
+        assert_eq!(sparse.completeness(), 0.25);
+        assert_eq!(rich.completeness(), 1.0);
+    }
+
+    #[test]
+    fn line_endings() {
+        let ris = RIS(vec![
+            ris_entry! { type: Journal, title: "A" },
+            ris_entry! { type: WholeBook },
+        ]);
+
+        assert_eq!(
+            ris.to_string_with_line_ending(LineEnding::Lf).as_bytes(),
+            &b"TY  - JOUR\nT1  - A\nER  - \nTY  - BOOK\nER  - "[..]
+        );
+        assert_eq!(
+            ris.to_string_with_line_ending(LineEnding::CrLf).as_bytes(),
+            &b"TY  - JOUR\r\nT1  - A\r\nER  - \r\nTY  - BOOK\r\nER  - "[..]
+        );
+        assert_eq!(
+            ris.to_string_with_line_ending(LineEnding::Lf),
+            ris.to_string()
+        );
+
+        let crlf = ris.to_string_with_line_ending(LineEnding::CrLf);
+        assert_eq!(RIS::from_str(&crlf).unwrap(), ris);
+    }
+
+    #[test]
+    fn dedup_keep_most_complete() {
+        let mut ris = RIS(vec![
+            Entry {
+                title: Some(String::from("On Computable Numbers")),
+                doi: Some(String::from("10.1112/plms/s2-42.1.230")),
+                volume: Some(String::from("42")),
+                ..Entry::new(ReferenceType::Journal)
+            },
+            Entry {
+                title: Some(String::from("A Mathematical Theory of Communication")),
+                ..Entry::new(ReferenceType::Journal)
+            },
+            Entry {
+                title: Some(String::from("On computable numbers.")),
+                authors: vec![String::from("Turing, Alan")],
+                primary_date: Some(PublicationDate::new(1937, None, None, None)),
+                journal: Some(String::from(
+                    "Proceedings of the London Mathematical Society",
+                )),
+                doi: Some(String::from("https://doi.org/10.1112/PLMS/s2-42.1.230")),
+                ..Entry::new(ReferenceType::Journal)
+            },
+            Entry {
+                title: Some(String::from("A mathematical theory of communication")),
+                authors: vec![String::from("Shannon, Claude E.")],
+                ..Entry::new(ReferenceType::Journal)
+            },
+            Entry::new(ReferenceType::Journal),
+            Entry::new(ReferenceType::Journal),
+        ]);
+
+        let merges = ris.dedup_keep_most_complete();
+
+        assert_eq!(
+            merges,
+            vec![
+                DedupMerge {
+                    kept: 2,
+                    removed: vec![0]
+                },
+                DedupMerge {
+                    kept: 3,
+                    removed: vec![1]
+                },
+            ]
+        );
+
+        assert_eq!(
+            ris.0,
+            vec![
+                Entry {
+                    title: Some(String::from("On computable numbers.")),
+                    authors: vec![String::from("Turing, Alan")],
+                    primary_date: Some(PublicationDate::new(1937, None, None, None)),
+                    journal: Some(String::from(
+                        "Proceedings of the London Mathematical Society"
+                    )),
+                    doi: Some(String::from("https://doi.org/10.1112/PLMS/s2-42.1.230")),
+                    volume: Some(String::from("42")),
+                    ..Entry::new(ReferenceType::Journal)
+                },
+                Entry {
+                    title: Some(String::from("A mathematical theory of communication")),
+                    authors: vec![String::from("Shannon, Claude E.")],
+                    ..Entry::new(ReferenceType::Journal)
+                },
+                Entry::new(ReferenceType::Journal),
+                Entry::new(ReferenceType::Journal),
+            ]
+        );
+    }
+
+    #[test]
+    fn unpadded_dates() {
+        let ris = RIS(vec![Entry {
+            primary_date: Some(PublicationDate::new(1995, Some(3), Some(1), None)),
+            secondary_date: Some(PublicationDate::new(
+                2001,
+                Some(11),
+                None,
+                Some(String::from("Fall")),
+            )),
+            ..Entry::new(ReferenceType::Journal)
+        }]);
+
+        let padded = ris.to_string();
+        let unpadded = ris.to_string_with_options(&SerializeOptions {
+            unpadded_dates: true,
+            ..SerializeOptions::default()
+        });
+
+        assert!(padded.contains("Y1  - 1995/03/01/\n"));
+        assert!(padded.contains("Y2  - 2001/11//Fall\n"));
+        assert!(unpadded.contains("Y1  - 1995/3/1/\n"));
+        assert!(unpadded.contains("Y2  - 2001/11//Fall\n"));
+
+        assert_eq!(padded.parse::<RIS>().unwrap(), ris);
+        assert_eq!(unpadded.parse::<RIS>().unwrap(), ris);
+    }
+
+    #[test]
+    fn from_entries() {
+        let ris = RIS::from_entries(vec![
+            EntryBuilder::new(ReferenceType::Journal)
+                .title("A Mathematical Theory of Communication")
+                .author("Shannon, Claude E.")
+                .year(1948),
+            EntryBuilder::new(ReferenceType::WholeBook).title("The Art of Computer Programming"),
+        ]);
+
+        assert_eq!(
+            ris,
+            RIS(vec![
+                ris_entry! {
+                    type: Journal,
+                    title: "A Mathematical Theory of Communication",
+                    authors: ["Shannon, Claude E."],
+                    year: 1948,
+                },
+                ris_entry! { type: WholeBook, title: "The Art of Computer Programming" },
+            ])
+        );
+
+        let mixed: Vec<Entry> = vec![
+            EntryBuilder::new(ReferenceType::Generic)
+                .doi("10.1000/1")
+                .build(),
+            Entry::new(ReferenceType::Generic),
+        ];
+        let ris = RIS::from_entries(mixed.clone());
+
+        assert_eq!(ris.0, mixed);
+        assert_eq!(mixed.into_iter().collect::<RIS>(), ris);
+    }
+
+    #[test]
+    fn date_warnings() {
+        let entry = Entry {
+            primary_date: Some(PublicationDate::new(2918, None, None, None)),
+            secondary_date: Some(PublicationDate::new(1200, None, None, None)),
+            ..Entry::new(ReferenceType::Journal)
+        };
+
+        assert_eq!(
+            entry.date_warnings(),
+            vec![
+                DateWarning::FutureYear {
+                    field: "primary_date",
+                    year: 2918
+                },
+                DateWarning::ImplausiblyOld {
+                    field: "secondary_date",
+                    year: 1200
+                },
+            ]
+        );
+
+        let web_page = Entry {
+            primary_date: Some(PublicationDate::new(1900, None, None, None)),
+            ..Entry::new(ReferenceType::WebPage)
+        };
+        assert_eq!(
+            web_page.date_warnings(),
+            vec![DateWarning::ImplausiblyOld {
+                field: "primary_date",
+                year: 1900
+            }]
+        );
+
+        let ancient = Entry {
+            primary_date: Some(PublicationDate::new(200, None, None, None)),
+            ..Entry::new(ReferenceType::AncientText)
+        };
+        assert_eq!(ancient.date_warnings(), vec![]);
+
+        let recent = Entry {
+            primary_date: Some(PublicationDate::new(current_year(), None, None, None)),
+            ..Entry::new(ReferenceType::Journal)
+        };
+        assert_eq!(recent.date_warnings(), vec![]);
+        assert!(current_year() >= 2024);
+    }
+
+    #[test]
+    fn endnote_codes() {
+        assert_eq!(ReferenceType::Journal.to_endnote_code(), Some(17));
+        assert_eq!(ReferenceType::WholeBook.to_endnote_code(), Some(6));
+        assert_eq!(ReferenceType::BookChapter.to_endnote_code(), Some(5));
+        assert_eq!(
+            ReferenceType::ThesisOrDissertation.to_endnote_code(),
+            Some(32)
+        );
+        assert_eq!(ReferenceType::Slide.to_endnote_code(), None);
+        assert_eq!(
+            ReferenceType::Other(String::from("ODD")).to_endnote_code(),
+            None
+        );
+
+        assert_eq!(
+            ReferenceType::from_endnote_code(17),
+            Some(ReferenceType::Journal)
+        );
+        assert_eq!(
+            ReferenceType::from_endnote_code(12),
+            Some(ReferenceType::WebPage)
+        );
+        assert_eq!(ReferenceType::from_endnote_code(0), None);
+
+        for (reference_type, code) in ENDNOTE_CODES {
+            assert_eq!(reference_type.to_endnote_code(), Some(*code));
+            assert_eq!(
+                ReferenceType::from_endnote_code(*code).as_ref(),
+                Some(reference_type)
+            );
+        }
+    }
+
+    #[test]
+    fn er_without_trailing_space() {
+        let expected = RIS(vec![
+            Entry {
+                title: Some(String::from("First")),
+                ..Entry::new(ReferenceType::Journal)
+            },
+            Entry {
+                title: Some(String::from("Second")),
+                ..Entry::new(ReferenceType::Journal)
+            },
+        ]);
+
+        for (er, end) in &[
+            ("ER  -", ""),
+            ("ER  - ", ""),
+            ("ER  - ", "\n"),
+            ("ER  -", "\n"),
+        ] {
+            let s = format!(
+                "TY  - JOUR\nT1  - First\n{er}\nTY  - JOUR\nT1  - Second\n{er}{end}",
+                er = er,
+                end = end
+            );
+
+            assert_eq!(s.parse::<RIS>().unwrap(), expected);
+            assert_eq!(
+                RIS::from_str_with_options(
+                    &s,
+                    &ParseOptions {
+                        multiline_values: true,
+                        ..ParseOptions::default()
+                    }
+                )
+                .unwrap(),
+                expected
+            );
+            assert_eq!(
+                parse_borrowed(&s)
+                    .unwrap()
+                    .iter()
+                    .map(EntryRef::to_owned)
+                    .collect::<Vec<_>>(),
+                expected.0
+            );
+        }
+    }
+
+    #[test]
+    fn try_build() {
+        let untitled = EntryBuilder::new(ReferenceType::Journal)
+            .author("Shannon, Claude E.")
+            .year(1948)
+            .journal("Bell System Technical Journal");
+
+        assert_eq!(
+            untitled.clone().try_build(),
+            Err(BuildError::MissingFields(vec!["title"]))
+        );
+        assert_eq!(untitled.clone().build().title, None);
+
+        let titled = untitled.title("A Mathematical Theory of Communication");
+        assert_eq!(titled.clone().try_build(), Ok(titled.clone().build()));
+
+        assert_eq!(
+            titled
+                .primary_date(PublicationDate::new(1948, Some(2), Some(30), None))
+                .try_build(),
+            Err(BuildError::InvalidDate("primary_date"))
+        );
+
+        assert!(PublicationDate::new(2000, Some(2), Some(29), None).is_valid());
+        assert!(!PublicationDate::new(1900, Some(2), Some(29), None).is_valid());
+        assert!(!PublicationDate::new(2000, Some(13), None, None).is_valid());
+        assert!(PublicationDate::new(2000, None, Some(31), None).is_valid());
+    }
+
+    #[test]
+    fn web_of_science_tags() {
+        let s = "TY  - JOUR
+AU  - Shannon, CE
+AF  - Shannon, Claude E.
+T1  - A Mathematical Theory of Communication
+CR  - Nyquist H, 1924, BELL SYST TECH J, V3, P324
+CR  - Hartley RVL, 1928, BELL SYST TECH J, V7, P535
+CR  - Nyquist H, 1928, T AM I ELECTR ENG, V47, P617
+ER  - ";
+
+        let ris: RIS = s.parse().unwrap();
+        let entry = &ris.0[0];
+
+        assert_eq!(entry.full_authors, vec!["Shannon, Claude E."]);
+        assert_eq!(
+            entry.cited_references,
+            vec![
+                "Nyquist H, 1924, BELL SYST TECH J, V3, P324",
+                "Hartley RVL, 1928, BELL SYST TECH J, V7, P535",
+                "Nyquist H, 1928, T AM I ELECTR ENG, V47, P617",
+            ]
+        );
+        assert_eq!(ris.to_string().parse::<RIS>().unwrap(), ris);
+        assert_eq!(parse_borrowed(s).unwrap()[0].to_owned(), *entry);
+    }
+
+    #[test]
+    fn round_trip_random_entries() {
+        // A small xorshift generator, so that the generated entries are the same on every run
+        let mut state: u64 = 0x2545_f491_4f6c_dd1d;
+        let mut next = move |n: u64| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state % n
+        };
+
+        const ALPHABET: &[char] = &[
+            'a', 'Z', '0', ' ', '-', '/', ',', '.', ':', ';', '"', '\\', 'é', '–', '\t',
+        ];
+
+        let reference_types: Vec<ReferenceType> = ENDNOTE_CODES
+            .iter()
+            .map(|(t, _)| t.clone())
+            .chain(vec![
+                ReferenceType::Abstract,
+                ReferenceType::InPress,
+                ReferenceType::JournalFull,
+                ReferenceType::Other(String::from("ODD")),
+            ])
+            .collect();
+
+        let string = |next: &mut dyn FnMut(u64) -> u64| -> String {
+            (0..next(12))
+                .map(|_| ALPHABET[next(ALPHABET.len() as u64) as usize])
+                .collect()
+        };
+        let option = |next: &mut dyn FnMut(u64) -> u64| {
+            if next(3) == 0 {
+                Some(string(next))
+            } else {
+                None
+            }
+        };
+
+        for _ in 0..500 {
+            let mut entry =
+                Entry::new(reference_types[next(reference_types.len() as u64) as usize].clone());
+
+            for field in [
+                &mut entry.id,
+                &mut entry.title,
+                &mut entry.secondary_title,
+                &mut entry.tertiary_title,
+                &mut entry.short_title,
+                &mut entry.notes,
+                &mut entry.abstract_,
+                &mut entry.reprint,
+                &mut entry.availability,
+                &mut entry.caption,
+                &mut entry.call_number,
+                &mut entry.classification,
+                &mut entry.doi,
+                &mut entry.accession_number,
+                &mut entry.start_page,
+                &mut entry.end_page,
+                &mut entry.journal,
+                &mut entry.journal_abbrev,
+                &mut entry.journal_abbrev_1,
+                &mut entry.journal_abbrev_2,
+                &mut entry.volume,
+                &mut entry.issue,
+                &mut entry.city,
+                &mut entry.publisher,
+                &mut entry.serial_number,
+                &mut entry.address,
+                &mut entry.workplace,
+                &mut entry.user_1,
+                &mut entry.user_2,
+                &mut entry.user_3,
+                &mut entry.user_4,
+                &mut entry.user_5,
+                &mut entry.custom_1,
+                &mut entry.custom_2,
+                &mut entry.custom_3,
+                &mut entry.custom_4,
+                &mut entry.custom_5,
+                &mut entry.custom_6,
+                &mut entry.custom_7,
+                &mut entry.custom_8,
+                &mut entry.misc_1,
+                &mut entry.misc_2,
+                &mut entry.misc_3,
+                &mut entry.wos_times_cited,
+                &mut entry.total_times_cited,
+            ] {
+                *field = option(&mut next);
+            }
+
+            for field in [
+                &mut entry.authors,
+                &mut entry.secondary_authors,
+                &mut entry.tertiary_authors,
+                &mut entry.full_authors,
+                &mut entry.keywords,
+                &mut entry.cited_references,
+                &mut entry.editors,
+                &mut entry.file_attachments,
+                &mut entry.urls,
+            ] {
+                *field = (0..next(4)).map(|_| string(&mut next)).collect();
+            }
+
+            for date in [&mut entry.primary_date, &mut entry.secondary_date] {
+                if next(2) == 0 {
+                    let mut part = |n| {
+                        if next(2) == 0 {
+                            Some(next(n) as i32)
+                        } else {
+                            None
+                        }
+                    };
+                    let (month, day) = (part(13), part(32));
+
+                    *date = Some(PublicationDate::new(
+                        next(10000) as i32,
+                        month,
+                        day,
+                        option(&mut next).filter(|s| !s.is_empty()),
+                    ));
+                }
+            }
+
+            let ris = RIS(vec![entry]);
+
+            for options in [
+                SerializeOptions::default(),
+                SerializeOptions {
+                    line_ending: LineEnding::CrLf,
+                    unpadded_dates: true,
+                    ..SerializeOptions::default()
+                },
+            ]
+            .iter()
+            {
+                let s = ris.to_string_with_options(options);
+
+                assert_eq!(s.parse::<RIS>().as_ref().ok(), Some(&ris), "{:?}", s);
+                assert_eq!(
+                    parse_borrowed(&s)
+                        .unwrap()
+                        .iter()
+                        .map(EntryRef::to_owned)
+                        .collect::<Vec<_>>(),
+                    ris.0
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn citation_short_title() {
+        let mut entry = ris_entry! {
+            type: Journal,
+            title: "A Mathematical Theory of Communication, Being a Very Long and Detailed Title for Testing Purposes",
+            short_title: "A Mathematical Theory of Communication",
+            authors: ["Shannon, Claude E."],
+            year: 1948,
+        };
+
+        assert_eq!(
+            entry.citation(),
+            "Shannon (1948). A Mathematical Theory of Communication."
+        );
+        assert_eq!(
+            entry.citation_with_threshold(200),
+            "Shannon (1948). A Mathematical Theory of Communication, Being a Very Long and Detailed Title for Testing Purposes."
+        );
+
+        entry.authors.push(String::from("Weaver, Warren"));
+        entry.title = Some(String::from("The Mathematical Theory of Communication."));
+        assert_eq!(
+            entry.citation(),
+            "Shannon and Weaver (1948). The Mathematical Theory of Communication."
+        );
+
+        entry.authors.push(String::from("Someone, Else"));
+        entry.primary_date = None;
+        assert_eq!(
+            entry.citation_with_threshold(10),
+            "Shannon et al. A Mathematical Theory of Communication."
+        );
+
+        assert_eq!(Entry::new(ReferenceType::Generic).citation(), "");
+    }
+
+    #[test]
+    fn page_span() {
+        let mut entry = Entry::new(ReferenceType::Journal);
+        assert_eq!(entry.page_span(), None);
+
+        entry.start_page = Some(String::from("379"));
+        assert_eq!(entry.page_span().as_deref(), Some("379"));
+
+        entry.end_page = Some(String::from("423"));
+        assert_eq!(entry.page_span().as_deref(), Some("379-423"));
+    }
+
+    #[test]
+    fn n2_as_notes() {
+        let s = "TY  - JOUR
+N1  - First note
+N2  - Second note
+ER  - ";
+
+        let ris: RIS = s.parse().unwrap();
+        assert_eq!(ris.0[0].notes.as_deref(), Some("First note"));
+        assert_eq!(ris.0[0].abstract_.as_deref(), Some("Second note"));
+
+        let options = ParseOptions {
+            n2_as_notes: true,
+            ..ParseOptions::default()
+        };
+        let ris = RIS::from_str_with_options(s, &options).unwrap();
+        assert_eq!(ris.0[0].notes.as_deref(), Some("First note\nSecond note"));
+        assert_eq!(ris.0[0].abstract_, None);
+
+        let ris =
+            RIS::from_str_with_options("TY  - JOUR\nAB  - Abstract\nN2  - Note\nER  - ", &options)
+                .unwrap();
+        assert_eq!(ris.0[0].notes.as_deref(), Some("Note"));
+        assert_eq!(ris.0[0].abstract_.as_deref(), Some("Abstract"));
+    }
+
+    #[test]
+    fn remove_empty() {
+        let mut ris: RIS = "TY  - JOUR
+ER  - 
+TY  - JOUR
+T1  - A Mathematical Theory of Communication
+ER  - 
+TY  - BOOK
+ER  - "
+            .parse()
+            .unwrap();
+
+        assert!(ris.0[0].is_empty());
+        assert!(!ris.0[1].is_empty());
+
+        assert_eq!(ris.remove_empty(), 2);
+        assert_eq!(ris.0.len(), 1);
+        assert_eq!(
+            ris.0[0].title.as_deref(),
+            Some("A Mathematical Theory of Communication")
+        );
+        assert_eq!(ris.remove_empty(), 0);
+    }
+
+    #[test]
+    fn lk_links() {
+        let entry: Entry = "TY  - ELEC
+UR  - https://example.com/paper
+LK  - https://example.org/mirror
+ER  - "
+            .parse()
+            .unwrap();
+
+        assert_eq!(
+            entry.urls,
+            vec!["https://example.com/paper", "https://example.org/mirror"]
+        );
+        assert_eq!(
+            entry.to_string(),
+            "TY  - ELEC
+UR  - https://example.com/paper
+UR  - https://example.org/mirror
+ER  - "
+        );
+    }
+
+    #[test]
+    fn cite_key() {
+        let mut entry = ris_entry! {
+            type: Journal,
+            title: "A Mathematical Theory of Communication",
+            authors: ["Shannon, Claude E.", "Weaver, Warren"],
+            year: 1948,
+        };
+        assert_eq!(entry.cite_key(), "shannon1948");
+
+        entry.authors = vec![String::from("O'Brien-Smith, J.")];
+        assert_eq!(entry.cite_key(), "obriensmith1948");
+
+        entry.authors.clear();
+        assert_eq!(entry.cite_key(), "mathematical1948");
+
+        entry.primary_date = None;
+        assert_eq!(entry.cite_key(), "mathematical");
+
+        entry.title = None;
+        assert_eq!(entry.cite_key(), "anon");
+    }
+
+    #[test]
+    fn finalize_at_eof() {
+        let s = "TY  - JOUR
+T1  - Complete
+ER  - 
+TY  - JOUR
+T1  - Truncated";
+
+        assert!(matches!(
+            s.parse::<RIS>().unwrap_err().kind,
+            ParseErrorKind::UnterminatedEntry
+        ));
+
+        let options = ParseOptions {
+            finalize_at_eof: true,
+            ..ParseOptions::default()
+        };
+        let ris = RIS::from_str_with_options(s, &options).unwrap();
+
+        assert_eq!(
+            ris.0.iter().map(|e| e.title.as_deref()).collect::<Vec<_>>(),
+            vec![Some("Complete"), Some("Truncated")]
+        );
+
+        let entries: Vec<_> = EntryReader::with_options(s.as_bytes(), options)
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(entries, ris.0);
+    }
+
+    #[test]
+    fn bibtex_types() {
+        use ReferenceType::*;
+
+        let all = vec![
+            Abstract,
+            AudiovisualMaterial,
+            AggregatedDatabase,
+            AncientText,
+            ArtWork,
+            Bill,
+            Blog,
+            WholeBook,
+            Case,
+            BookChapter,
+            Chart,
+            ClassicalWork,
+            ComputerProgram,
+            ConferenceProceeding,
+            ConferencePaper,
+            Catalog,
+            DataFile,
+            OnlineDatabase,
+            Dictionary,
+            ElectronicBook,
+            ElectronicBookSection,
+            EditedBook,
+            ElectronicArticle,
+            WebPage,
+            Encyclopedia,
+            Equation,
+            Figure,
+            Generic,
+            GovernmentDocument,
+            Grant,
+            Hearing,
+            InternetCommunication,
+            InPress,
+            JournalFull,
+            Journal,
+            LegalRuleOrRegulation,
+            Manuscript,
+            Map,
+            MagazineArticle,
+            MotionPicture,
+            OnlineMultimedia,
+            MusicScore,
+            Newspaper,
+            Pamphlet,
+            Patent,
+            PersonalCommunication,
+            Report,
+            SerialPublication,
+            Slide,
+            SoundRecording,
+            Standard,
+            Statute,
+            ThesisOrDissertation,
+            UnpublishedWork,
+            VideoRecording,
+            Other(String::from("ODD")),
+        ];
+
+        for reference_type in all {
+            assert!(!reference_type.bibtex_type().is_empty());
+        }
+
+        assert_eq!(Journal.bibtex_type(), "article");
+        assert_eq!(WholeBook.bibtex_type(), "book");
+        assert_eq!(BookChapter.bibtex_type(), "incollection");
+        assert_eq!(ConferencePaper.bibtex_type(), "inproceedings");
+        assert_eq!(ThesisOrDissertation.bibtex_type(), "phdthesis");
+        assert_eq!(WebPage.bibtex_type(), "misc");
+        assert_eq!(Other(String::from("ODD")).bibtex_type(), "misc");
+    }
+
+    #[test]
+    fn form_feeds() {
+        struct Noop;
+        impl RisVisitor for Noop {}
+
+        let expected = RIS(vec![
+            ris_entry! { type: Journal, title: "First" },
+            ris_entry! { type: Journal, title: "Second" },
+        ]);
+
+        for s in &[
+            "TY  - JOUR\nT1  - First\nER  - \n\x0C\nTY  - JOUR\nT1  - Second\nER  - \n\x0C",
+            "TY  - JOUR\nT1  - First\nER  - \x0C\n\x0CTY  - JOUR\nT1  - Second\nER  - ",
+        ] {
+            assert_eq!(s.parse::<RIS>().unwrap(), expected);
+            assert_eq!(
+                parse_borrowed(s)
+                    .unwrap()
+                    .iter()
+                    .map(EntryRef::to_owned)
+                    .collect::<Vec<_>>(),
+                expected.0
+            );
+            assert!(parse_with_visitor(s, &mut Noop).is_ok());
+        }
+    }
+
+    #[test]
+    fn entry_to_json() {
+        let entry = ris_entry! {
+            type: Journal,
+            title: "\"Quoted\" \\ back\tslash\u{1}",
+            keywords: ["naïve", "line\r\nbreak"],
+            year: 1948,
+        };
+
+        assert_eq!(
+            entry.to_json(),
+            r#"{"reference_type":"JOUR","title":"\"Quoted\" \\ back\tslash\u0001","primary_date":"1948///","keywords":["naïve","line\r\nbreak"]}"#
+        );
+        assert_eq!(
+            Entry::new(ReferenceType::Generic).to_json(),
+            r#"{"reference_type":"GEN"}"#
+        );
+        assert_eq!(
+            RIS(vec![entry.clone()]).to_json(),
+            format!("[{}]", entry.to_json())
+        );
+    }
+
+    #[test]
+    fn concat_repeated_unique_tags() {
+        let s = "TY  - JOUR
+AB  - The first half of the abstract,
+AB  - and the second half.
+ER  - ";
+
+        assert!(matches!(
+            s.parse::<RIS>().unwrap_err().kind,
+            ParseErrorKind::DuplicateField
+        ));
+
+        let options = ParseOptions {
+            concat_repeated_unique_tags: true,
+            ..ParseOptions::default()
+        };
+        let ris = RIS::from_str_with_options(s, &options).unwrap();
+        assert_eq!(
+            ris.0[0].abstract_.as_deref(),
+            Some("The first half of the abstract, and the second half.")
+        );
+
+        assert!(
+            RIS::from_str_with_options("TY  - JOUR\nY2  - 1948\nY2  - 1949\nER  - ", &options)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn structure_validation() {
+        assert!(validate_structure(
+            "TY  - JOUR\nXX  - Unknown tags are fine\nER  - \nTY  - BOOK\nER  -"
+        )
+        .is_ok());
+        assert!(validate_structure("").is_ok());
+
+        let unterminated =
+            validate_structure("TY  - JOUR\nT1  - Title\nTY  - JOUR\nER  - ").unwrap_err();
+        assert_eq!(unterminated.line_no, 3);
+        assert!(matches!(
+            unterminated.kind,
+            ParseErrorKind::UnterminatedEntry
+        ));
+
+        let outside = validate_structure("T1  - Title\n").unwrap_err();
+        assert!(matches!(outside.kind, ParseErrorKind::TagOutsideEntry));
+
+        let malformed = validate_structure("TY  - JOUR\nnot a tag\nER  - ").unwrap_err();
+        assert_eq!(malformed.line_no, 2);
+        assert!(matches!(malformed.kind, ParseErrorKind::InvalidLine));
+
+        let truncated = validate_structure("TY  - JOUR\nT1  - Title").unwrap_err();
+        assert!(matches!(truncated.kind, ParseErrorKind::UnterminatedEntry));
+    }
+
+    #[test]
+    fn split_authors_on_semicolon() {
+        let s = "TY  - JOUR
+AU  - Shannon, Claude E.; Weaver, Warren;
+AU  - Someone, Else
+ER  - ";
+
+        let ris: RIS = s.parse().unwrap();
+        assert_eq!(
+            ris.0[0].authors,
+            vec!["Shannon, Claude E.; Weaver, Warren;", "Someone, Else"]
+        );
+
+        let options = ParseOptions {
+            split_authors_on_semicolon: true,
+            ..ParseOptions::default()
+        };
+        let ris = RIS::from_str_with_options(s, &options).unwrap();
+        assert_eq!(
+            ris.0[0].authors,
+            vec!["Shannon, Claude E.", "Weaver, Warren", "Someone, Else"]
+        );
+    }
+
+    #[test]
+    fn assign_unique_cite_keys() {
+        let mut ris = RIS(vec![
+            ris_entry! { type: Journal, authors: ["Shannon, Claude E."], year: 1948 },
+            ris_entry! { type: Journal, authors: ["Turing, Alan"], year: 1936, id: "old" },
+            ris_entry! { type: Journal, authors: ["Shannon, C."], year: 1948 },
+        ]);
+
+        ris.assign_unique_cite_keys();
+
+        assert_eq!(
+            ris.0.iter().map(|e| e.id.as_deref()).collect::<Vec<_>>(),
+            vec![
+                Some("shannon1948a"),
+                Some("turing1936"),
+                Some("shannon1948b")
+            ]
+        );
+
+        assert_eq!(alphabetic_suffix(1), "a");
+        assert_eq!(alphabetic_suffix(26), "z");
+        assert_eq!(alphabetic_suffix(27), "aa");
+        assert_eq!(alphabetic_suffix(28), "ab");
+    }
+
+    #[test]
+    fn web_of_science_citation_counts() {
+        let s = "TY  - JOUR
+T1  - A Mathematical Theory of Communication
+A1  - Shannon, CE
+TC  - 12345
+Z9  - 23456
+ER  - ";
+
+        let ris: RIS = s.parse().unwrap();
+        assert_eq!(ris.0[0].wos_times_cited.as_deref(), Some("12345"));
+        assert_eq!(ris.0[0].total_times_cited.as_deref(), Some("23456"));
+        assert_eq!(ris.to_string(), s);
+    }
+
+    #[test]
+    fn cmp_by_date() {
+        let undated = ris_entry! { type: Journal, title: "Undated" };
+        let year = ris_entry! { type: Journal, title: "Year", year: 1948 };
+        let month = Entry {
+            primary_date: Some(PublicationDate::new(1948, Some(7), None, None)),
+            ..ris_entry! { type: Journal, title: "Month" }
+        };
+        let older = ris_entry! { type: Journal, title: "Older", year: 1936 };
+
+        assert_eq!(year.cmp_by_date(&month), Ordering::Less);
+        assert_eq!(undated.cmp_by_date(&older), Ordering::Greater);
+        assert_eq!(undated.cmp_by_date(&undated), Ordering::Equal);
+
+        let mut entries = vec![undated.clone(), month.clone(), older.clone(), year.clone()];
+        entries.sort_by(|a, b| a.cmp_by_date(b).then_with(|| a.title.cmp(&b.title)));
+        assert_eq!(
+            entries,
+            vec![older.clone(), year.clone(), month.clone(), undated.clone()]
+        );
+
+        let mut ris = RIS(vec![
+            undated.clone(),
+            month.clone(),
+            older.clone(),
+            year.clone(),
+        ]);
+        ris.sort_by_date();
+        assert_eq!(ris.0, vec![older, year, month, undated]);
+    }
+
+    #[test]
+    fn indented_continuation_lines() {
+        let s = "TY  - JOUR
+AB  - The first line of the abstract
+      and its indented continuation,
+\tand a tab-indented one.
+ER  - ";
+
+        let options = ParseOptions {
+            multiline_values: true,
+            ..ParseOptions::default()
+        };
+        let ris = RIS::from_str_with_options(s, &options).unwrap();
+
+        assert_eq!(
+            ris.0[0].abstract_.as_deref(),
+            Some("The first line of the abstract\nand its indented continuation,\nand a tab-indented one.")
+        );
+    }
+
+    #[test]
+    fn truncate_fields() {
+        let mut entry = ris_entry! {
+            type: Journal,
+            title: "Short",
+            abstract_: "Ünïcödé välüés",
+            keywords: ["ab", "abcdef"],
+        };
+
+        entry.truncate_fields(5);
+
+        assert_eq!(entry.title.as_deref(), Some("Short"));
+        assert_eq!(entry.abstract_.as_deref(), Some("Ünïc…"));
+        assert_eq!(entry.keywords, vec!["ab", "abcd…"]);
+
+        entry.truncate_fields(0);
+        assert_eq!(entry.title.as_deref(), Some(""));
+    }
+
+    #[test]
+    fn article_number() {
+        let mut entry = ris_entry! { type: Journal, start_page: "e0234567" };
+        assert_eq!(entry.article_number(), Some("e0234567"));
+
+        entry.end_page = Some(String::from("e0234570"));
+        assert_eq!(entry.article_number(), None);
+
+        for page in &["379", "xiv", "XIV"] {
+            let entry = ris_entry! { type: Journal, start_page: *page };
+            assert_eq!(entry.article_number(), None);
+        }
+
+        assert_eq!(Entry::new(ReferenceType::Journal).article_number(), None);
+    }
+
+    #[test]
+    fn field_presence() {
+        let ris = RIS(vec![
+            ris_entry! { type: Journal, title: "First", authors: ["A", "B"] },
+            ris_entry! { type: Journal, title: "Second", year: 1948 },
+            ris_entry! { type: Journal, authors: ["C"] },
+        ]);
+
+        let presence = ris.field_presence();
+
+        assert_eq!(presence["title"], 2);
+        assert_eq!(presence["authors"], 2);
+        assert_eq!(presence["primary_date"], 1);
+        assert_eq!(presence["doi"], 0);
+        assert_eq!(
+            presence.len(),
+            Entry::new(ReferenceType::Generic).fields().len()
+        );
+    }
+
+    #[test]
+    fn lowercase_reference_types() {
+        let ris: RIS = "TY  - jour\nT1  - Lowercase\nER  - \nTY  - Chap\nER  - \nTY  - odd\nER  - "
+            .parse()
+            .unwrap();
+
+        assert_eq!(ris.0[0].reference_type, ReferenceType::Journal);
+        assert_eq!(ris.0[1].reference_type, ReferenceType::BookChapter);
+        assert_eq!(
+            ris.0[2].reference_type,
+            ReferenceType::Other(String::from("odd"))
+        );
+        assert!(ris.to_string().starts_with("TY  - JOUR\n"));
+    }
+
+    #[test]
+    fn venue_hierarchy() {
+        let ris: RIS = "TY  - CHAP\nT1  - A Chapter\nT2  - A Book\nT3  - A Series\nER  - \nTY  - CHAP\nT1  - A Chapter\nT2  - \nT3  - A Series\nER  - "
+            .parse()
+            .unwrap();
+
+        assert_eq!(
+            ris.0[0].venue_hierarchy(),
+            vec!["A Chapter", "A Book", "A Series"]
+        );
+        assert_eq!(ris.0[1].venue_hierarchy(), vec!["A Chapter", "A Series"]);
+        assert!(Entry::new(ReferenceType::Generic)
+            .venue_hierarchy()
+            .is_empty());
+    }
+
+    #[test]
+    fn serialize_without_id() {
+        let ris: RIS = "TY  - JOUR\nID  - smith2000\nT1  - A Title\nER  - "
+            .parse()
+            .unwrap();
+
+        assert!(ris.to_string().contains("ID  - smith2000\n"));
+
+        let s = ris.to_string_with_options(&SerializeOptions {
+            emit_id: false,
+            ..SerializeOptions::default()
+        });
+
+        assert_eq!(s, "TY  - JOUR\nT1  - A Title\nER  - ");
+    }
+
+    #[test]
+    fn export_ndjson() {
+        let ris = RIS(vec![
+            ris_entry! {
+                type: Journal,
+                title: "Multi\nline",
+                authors: ["Shannon, Claude E."],
+            },
+            Entry::new(ReferenceType::Generic),
+            ris_entry! {
+                type: WholeBook,
+                title: "Second",
+            },
+        ]);
+
+        let ndjson = ris.to_ndjson();
+        let lines: Vec<_> = ndjson.lines().collect();
+
+        assert_eq!(lines.len(), ris.0.len());
+        assert!(ndjson.ends_with('\n'));
+
+        for (line, entry) in lines.iter().zip(&ris.0) {
+            assert!(line.starts_with('{') && line.ends_with('}'));
+            assert_eq!(*line, entry.to_json());
+        }
+
+        assert_eq!(format!("[{}]", lines.join(",")), ris.to_json());
+        assert_eq!(RIS(vec![]).to_ndjson(), "");
+    }
+
+    #[test]
+    fn extract_doi() {
+        let mut entry = ris_entry! {
+            type: Journal,
+            doi: "https://doi.org/10.1000/182",
+            notes: "doi: 10.1000/999",
+        };
+        assert_eq!(entry.extract_doi().as_deref(), Some("10.1000/182"));
+
+        entry.doi = None;
+        assert_eq!(entry.extract_doi().as_deref(), Some("10.1000/999"));
+        assert_eq!(entry.notes.as_deref(), Some("doi: 10.1000/999"));
+
+        entry.notes = Some(String::from("Published online"));
+        entry.misc_3 = Some(String::from("(DOI: 10.1234.5/abc-def.)"));
+        assert_eq!(entry.extract_doi().as_deref(), Some("10.1234.5/abc-def"));
+
+        entry.misc_3 = Some(String::from("Not a DOI: 10.12/x"));
+        assert_eq!(entry.extract_doi(), None);
+    }
+
+    #[test]
+    fn skip_empty_entries() {
+        let s = "TY  - GEN\nER  - \nTY  - JOUR\nT1  - A Title\nER  - \nTY  - BOOK\nER  - ";
+
+        assert_eq!(RIS::from_str(s).unwrap().0.len(), 3);
+
+        let ris = RIS::from_str_with_options(
+            s,
+            &ParseOptions {
+                skip_empty_entries: true,
+                ..ParseOptions::default()
+            },
+        )
+        .unwrap();
+
+        assert_eq!(ris.0.len(), 1);
+        assert_eq!(ris.0[0].title.as_deref(), Some("A Title"));
+    }
+
+    #[test]
+    fn issue_number() {
+        let mut entry = ris_entry! {
+            type: BookChapter,
+            issue: "4",
+            misc_1: "7",
+        };
+        assert_eq!(entry.issue_number(), Some("4"));
+
+        entry.issue = None;
+        assert_eq!(entry.issue_number(), Some("7"));
+
+        entry.issue = Some(String::new());
+        assert_eq!(entry.issue_number(), Some("7"));
+
+        entry.misc_1 = None;
+        assert_eq!(entry.issue_number(), None);
+    }
+
+    #[test]
+    fn reference_type_listing() {
+        let s = "TY  - JOUR\nT1  - First\nER  - \nTY  - chap\nAU  - Shannon, Claude E.\nER  - \nTY  - ODD\nER  - ";
+        let types: Vec<_> = RIS::from_str(s)
+            .unwrap()
+            .0
+            .into_iter()
+            .map(|entry| entry.reference_type)
+            .collect();
+
+        assert_eq!(list_reference_types(s).unwrap(), types);
+        assert_eq!(
+            types,
+            vec![
+                ReferenceType::Journal,
+                ReferenceType::BookChapter,
+                ReferenceType::Other(String::from("ODD"))
+            ]
+        );
+        assert!(list_reference_types("TY  - JOUR\nT1  - Unterminated").is_err());
+    }
+
+    #[test]
+    fn multiline_tag_like_continuation() {
+        let s = "TY  - JOUR\nAB  - We study\nDNA  - sequence alignment\nDN  - sequencing\nKW  - dna\nER  - ";
+        let options = ParseOptions {
+            multiline_values: true,
+            ..ParseOptions::default()
+        };
+
+        let ris = RIS::from_str_with_options(s, &options).unwrap();
+        assert_eq!(
+            ris.0[0].abstract_.as_deref(),
+            Some("We study\nDNA  - sequence alignment\nDN  - sequencing")
+        );
+        assert_eq!(ris.0[0].keywords, vec!["dna"]);
+
+        let ris = RIS::from_str_with_options(
+            s,
+            &ParseOptions {
+                preserve_unknown_tags: true,
+                ..options
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            ris.0[0].unknown_tags,
+            vec![(String::from("DN"), String::from("sequencing"))]
+        );
+    }
+
+    #[test]
+    fn known_tags_are_parsed() {
+        for tag in KNOWN_TAGS.iter().filter(|tag| !["TY", "ER"].contains(tag)) {
+            let value = if ["Y1", "PY", "DA", "Y2"].contains(tag) {
+                "2000"
+            } else {
+                "value"
+            };
+
+            assert!(
+                Entry::new(ReferenceType::Generic)
+                    .set_field(tag, value, 1, &ParseOptions::default())
+                    .is_ok(),
+                "{}",
+                tag
+            );
+        }
+    }
+
+    #[test]
+    fn append() {
+        let mut ris = RIS(vec![Entry::new(ReferenceType::Journal)]);
+        let mut other = RIS(vec![
+            Entry::new(ReferenceType::WholeBook),
+            Entry::new(ReferenceType::Journal),
+        ]);
+
+        ris.append(&mut other);
+
+        assert!(other.0.is_empty());
+        assert_eq!(
+            ris.0
+                .iter()
+                .map(|entry| &entry.reference_type)
+                .collect::<Vec<_>>(),
+            vec![
+                &ReferenceType::Journal,
+                &ReferenceType::WholeBook,
+                &ReferenceType::Journal
+            ]
+        );
+    }
+
+    #[test]
+    fn resource_urls() {
+        let ris: RIS = "TY  - JOUR
+DO  - doi:10.1000/182
+AN  - PMID: 12345678
+UR  - https://example.com/article
+UR  - https://doi.org/10.1000/182
+L1  - file:///papers/article.pdf
+ER  - "
+            .parse()
+            .unwrap();
+
+        assert_eq!(
+            ris.0[0].resource_urls(),
+            vec![
+                "https://doi.org/10.1000/182",
+                "https://pubmed.ncbi.nlm.nih.gov/12345678/",
+                "https://example.com/article",
+                "file:///papers/article.pdf",
+            ]
+        );
+        assert_eq!(
+            ris.0[0].file_attachments,
+            vec!["file:///papers/article.pdf"]
+        );
+
+        let entry = ris_entry! {
+            type: Journal,
+            accession_number: "WOS:000123456700001",
+        };
+        assert!(entry.resource_urls().is_empty());
+    }
+
+    #[test]
+    fn best_journal_abbrev() {
+        let mut entry = ris_entry! {
+            type: Journal,
+            journal_abbrev: "Bell Syst. Tech. J.",
+            journal_abbrev_1: "BSTJ",
+            journal_abbrev_2: "Bell Syst Tech J",
+        };
+        assert_eq!(entry.best_journal_abbrev(), Some("Bell Syst. Tech. J."));
+
+        entry.journal_abbrev = Some(String::new());
+        assert_eq!(entry.best_journal_abbrev(), Some("Bell Syst Tech J"));
+
+        entry.journal_abbrev_2 = None;
+        assert_eq!(entry.best_journal_abbrev(), Some("BSTJ"));
+
+        assert_eq!(
+            Entry::new(ReferenceType::Journal).best_journal_abbrev(),
+            None
+        );
+    }
+
+    #[test]
+    fn allow_ty_anywhere() {
+        let s = "ID  - first\nPY  - 2000\nTY  - JOUR\nT1  - A Title\nER  - \nID  - second\nTY  - BOOK\nER  - ";
+        let options = ParseOptions {
+            allow_ty_anywhere: true,
+            ..ParseOptions::default()
+        };
+
+        let err = RIS::from_str(s).unwrap_err();
+        assert_eq!(err.line_no, 1);
+        assert!(matches!(err.kind, ParseErrorKind::TagOutsideEntry));
+
+        let ris = RIS::from_str_with_options(s, &options).unwrap();
+        assert_eq!(ris.0.len(), 2);
+        assert_eq!(ris.0[0].id.as_deref(), Some("first"));
+        assert_eq!(
+            ris.0[0].primary_date,
+            Some(PublicationDate::new(2000, None, None, None))
+        );
+        assert_eq!(ris.0[0].title.as_deref(), Some("A Title"));
+        assert_eq!(ris.0[1].id.as_deref(), Some("second"));
+
+        let err =
+            RIS::from_str_with_options("TY  - JOUR\nER  - \nID  - dangling", &options).unwrap_err();
+        assert_eq!(err.line_no, 3);
+        assert!(matches!(err.kind, ParseErrorKind::TagOutsideEntry));
+    }
+
+    #[test]
+    fn first_and_other_authors() {
+        let mut entry = ris_entry! {
+            type: Journal,
+            authors: ["Shannon, Claude E.", "Weaver, Warren", "Turing, Alan"],
+        };
+
+        assert_eq!(entry.first_author(), Some("Shannon, Claude E."));
+        assert_eq!(entry.other_authors(), ["Weaver, Warren", "Turing, Alan"]);
+
+        entry.authors.truncate(1);
+        assert_eq!(entry.first_author(), Some("Shannon, Claude E."));
+        assert!(entry.other_authors().is_empty());
+
+        entry.authors.clear();
+        assert_eq!(entry.first_author(), None);
+        assert!(entry.other_authors().is_empty());
+    }
+
+    #[test]
+    fn keywords_by_scheme() {
+        let entry = ris_entry! {
+            type: Journal,
+            keywords: [
+                "MeSH: Neoplasms",
+                "information theory",
+                "MeSH:Humans",
+                "Breast cancer: screening",
+                "ACM-CCS: Mathematics of computing",
+                "MeSH:",
+            ],
+        };
+
+        let groups = entry.keywords_by_scheme();
+
+        assert_eq!(groups.len(), 3);
+        assert_eq!(
+            groups[&None],
+            vec!["information theory", "Breast cancer: screening", "MeSH:"]
+        );
+        assert_eq!(
+            groups[&Some(String::from("MeSH"))],
+            vec!["Neoplasms", "Humans"]
+        );
+        assert_eq!(
+            groups[&Some(String::from("ACM-CCS"))],
+            vec!["Mathematics of computing"]
+        );
+        assert_eq!(entry.keywords.len(), 6);
+    }
+
+    #[test]
+    fn unknown_tags_with_digits() {
+        let lenient = ParseOptions {
+            preserve_unknown_tags: true,
+            ..ParseOptions::default()
+        };
+
+        for tag in &["A9", "Z1", "Q2"] {
+            let s = format!("TY  - JOUR\n{}  - value\nER  - ", tag);
+
+            let err = RIS::from_str(&s).unwrap_err();
+            assert_eq!(err.line_no, 2);
+            assert!(matches!(err.kind, ParseErrorKind::InvalidKey(ref key) if key == tag));
+
+            let ris = RIS::from_str_with_options(&s, &lenient).unwrap();
+            assert_eq!(
+                ris.0[0].unknown_tags,
+                vec![(tag.to_string(), String::from("value"))]
+            );
+            assert_eq!(ris.to_string(), s);
+        }
+
+        for line in &["9A  - value", "a9  - value", "A-  - value"] {
+            let s = format!("TY  - JOUR\n{}\nER  - ", line);
+            let err = RIS::from_str_with_options(&s, &lenient).unwrap_err();
+            assert!(matches!(err.kind, ParseErrorKind::InvalidLine));
+        }
+    }
+
+    #[test]
+    fn redundant_url() {
+        let mut entry = ris_entry! {
+            type: Journal,
+            doi: "10.1000/ABC",
+            urls: ["https://example.com/article"],
+        };
+        assert!(!entry.redundant_url());
+
+        entry
+            .urls
+            .push(String::from("HTTP://dx.doi.org/10.1000/abc"));
+        assert!(entry.redundant_url());
+
+        entry.urls = vec![String::from("https://doi.org/10.1000/other")];
+        assert!(!entry.redundant_url());
+
+        entry.urls.clear();
+        entry.file_attachments = vec![String::from("https://doi.org/10.1000/abc")];
+        assert!(entry.redundant_url());
+
+        entry.doi = None;
+        assert!(!entry.redundant_url());
+    }
+
+    #[cfg(feature = "std-threads")]
+    #[test]
+    fn parallel_parsing() {
+        let entry = "TY  - JOUR\nT1  - A Title\nAU  - Shannon, Claude E.\nER  - \n";
+        let s: String = (0..100)
+            .map(|i| format!("{}\nTY  - BOOK\nID  - {}\nER  - \n", entry, i))
+            .collect();
+
+        let chunks = split_entry_chunks(&s, 7);
+        assert!(chunks.len() > 1 && chunks.len() <= 8);
+        assert_eq!(chunks.iter().map(|(_, c)| *c).collect::<String>(), s);
+
+        let serial = RIS::from_str(&s).unwrap();
+        assert_eq!(serial.0.len(), 200);
+        assert_eq!(RIS::from_str_in_chunks(&s, 7).unwrap(), serial);
+        assert_eq!(RIS::from_str_parallel(&s).unwrap(), serial);
+
+        let broken = s.replacen("ID  - 80", "XX  - 80", 1);
+        let serial_err = RIS::from_str(&broken).unwrap_err();
+        let parallel_err = RIS::from_str_in_chunks(&broken, 7).unwrap_err();
+        assert_eq!(parallel_err.line_no, serial_err.line_no);
+        assert!(matches!(parallel_err.kind, ParseErrorKind::InvalidKey(_)));
+
+        let unterminated = format!("{}TY  - JOUR\nT1  - Unterminated", s);
+        assert_eq!(
+            RIS::from_str_in_chunks(&unterminated, 7)
+                .unwrap_err()
+                .line_no,
+            RIS::from_str(&unterminated).unwrap_err().line_no
+        );
+    }
+
+    #[test]
+    fn editors_round_trip() {
+        let s = "TY  - CHAP
+T1  - A Chapter
+A2  - Series, Editor
+ED  - Book, Editor
+ED  - Other, Editor
+ER  - ";
+
+        let ris: RIS = s.parse().unwrap();
+
+        assert_eq!(ris.0[0].secondary_authors, vec!["Series, Editor"]);
+        assert_eq!(ris.0[0].editors, vec!["Book, Editor", "Other, Editor"]);
+        assert_eq!(ris.to_string(), s);
+    }
+
+    #[test]
+    fn abstract_paragraphs() {
+        let mut entry = Entry::new(ReferenceType::Journal);
+        assert!(entry.abstract_paragraphs().is_empty());
+
+        entry.set_abstract_paragraphs(&["First paragraph.", "Second\nparagraph."]);
+        assert_eq!(
+            entry.abstract_.as_deref(),
+            Some("First paragraph.\n\nSecond\nparagraph.")
+        );
+        assert_eq!(
+            entry.abstract_paragraphs(),
+            vec!["First paragraph.", "Second\nparagraph."]
+        );
+
+        let options = ParseOptions {
+            multiline_values: true,
+            ..ParseOptions::default()
+        };
+        let ris =
+            RIS::from_str_with_options(&RIS(vec![entry.clone()]).to_string(), &options).unwrap();
+        assert_eq!(ris.0[0], entry);
+
+        entry.abstract_ = Some(String::from(" One.\n\n\n\nTwo. \n\n"));
+        assert_eq!(entry.abstract_paragraphs(), vec!["One.", "Two."]);
+
+        entry.set_abstract_paragraphs(&[]);
+        assert_eq!(entry.abstract_, None);
+    }
+
+    #[test]
+    fn bom_and_blank_lines() {
+        let s = "\u{FEFF}TY  - JOUR\r\nT1  - A Title\r\nER  - \r\n\r\n\r\nTY  - BOOK\r\nER  - \r\n";
+
+        let ris: RIS = s.parse().unwrap();
+        assert_eq!(ris.0.len(), 2);
+        assert_eq!(ris.0[0].reference_type, ReferenceType::Journal);
+        assert_eq!(ris.0[0].title.as_deref(), Some("A Title"));
+
+        assert_eq!(RIS::from_reader(s.as_bytes()).unwrap(), ris);
+        assert_eq!(parse_borrowed(s).unwrap().len(), 2);
+        assert!(validate_structure(s).is_ok());
+
+        let options = ParseOptions {
+            multiline_values: true,
+            ..ParseOptions::default()
+        };
+        assert_eq!(RIS::from_str_with_options(s, &options).unwrap(), ris);
+    }
+
+    #[test]
+    fn canonicalize_types() {
+        let mut ris = RIS(["jour ", " Chap", "BOOK", "\tconf\t", "odd "]
+            .iter()
+            .map(|t| Entry::new(ReferenceType::Other(t.to_string())))
+            .chain(std::iter::once(Entry::new(ReferenceType::Journal)))
+            .collect());
+
+        ris.canonicalize_types();
+
+        assert_eq!(
+            ris.0
+                .into_iter()
+                .map(|entry| entry.reference_type)
+                .collect::<Vec<_>>(),
+            vec![
+                ReferenceType::Journal,
+                ReferenceType::BookChapter,
+                ReferenceType::WholeBook,
+                ReferenceType::ConferenceProceeding,
+                ReferenceType::Other(String::from("odd ")),
+                ReferenceType::Journal,
+            ]
+        );
+    }
+
+    #[test]
+    fn page_count() {
+        let mut entry = ris_entry! {
+            type: Report,
+            start_page: "45 p.",
+        };
+        assert_eq!(entry.page_count(), Some(45));
+        assert_eq!(entry.start_page.as_deref(), Some("45 p."));
+
+        for (start_page, count) in &[
+            ("120 pp.", Some(120)),
+            (" 8P. ", Some(8)),
+            ("45", None),
+            ("p. 45", None),
+            ("45 p. ill.", None),
+        ] {
+            entry.start_page = Some(start_page.to_string());
+            assert_eq!(entry.page_count(), *count, "{}", start_page);
+        }
+
+        entry.start_page = None;
+        assert_eq!(entry.page_count(), None);
+    }
+
+    #[test]
+    fn five_authors_round_trip() {
+        let authors = [
+            "Shannon, Claude E.",
+            "Weaver, Warren",
+            "Turing, Alan",
+            "Lovelace, Ada",
+            "Hopper, Grace",
+        ];
+        let ris = RIS(vec![ris_entry! {
+            type: Journal,
+            authors: authors.iter().copied(),
+        }]);
+
+        let s = ris.to_string();
+        let lines: Vec<_> = s
+            .lines()
+            .filter_map(|line| line.strip_prefix("A1  - "))
+            .collect();
+        assert_eq!(lines, authors);
+
+        for options in &[
+            ParseOptions::default(),
+            ParseOptions {
+                multiline_values: true,
+                split_authors_on_semicolon: true,
+                ..ParseOptions::default()
+            },
+        ] {
+            let parsed = RIS::from_str_with_options(&s, options).unwrap();
+            assert_eq!(parsed, ris);
+            assert_eq!(parsed.to_string(), s);
+        }
+    }
+
+    #[test]
+    fn tag_aliases() {
+        let aliases: HashMap<_, _> = vec![("XA", "AU"), ("XT", "T1"), ("XB", "XA"), ("XE", "ER")]
+            .into_iter()
+            .map(|(alias, tag)| (String::from(alias), String::from(tag)))
+            .collect();
+        let options = ParseOptions::with_tag_aliases(aliases);
+
+        let ris = RIS::from_str_with_options(
+            "TY  - JOUR\nXT  - A Title\nXA  - Shannon, Claude E.\nAU  - Weaver, Warren\nER  - ",
+            &options,
+        )
+        .unwrap();
+        assert_eq!(ris.0[0].title.as_deref(), Some("A Title"));
+        assert_eq!(
+            ris.0[0].authors,
+            vec!["Shannon, Claude E.", "Weaver, Warren"]
+        );
+        assert_eq!(
+            ris.to_string(),
+            "TY  - JOUR\nT1  - A Title\nA1  - Shannon, Claude E.\nA1  - Weaver, Warren\nER  - "
+        );
+
+        for tag in &["XB", "XE", "XZ"] {
+            let s = format!("TY  - JOUR\n{}  - value\nER  - ", tag);
+            let err = RIS::from_str_with_options(&s, &options).unwrap_err();
+            assert!(matches!(err.kind, ParseErrorKind::InvalidKey(ref key) if key == tag));
+        }
+    }
+
+    #[test]
+    fn entry_into_builder() {
+        let entry: Entry =
+            "TY  - JOUR\nT1  - Old Title\nA1  - Shannon, Claude E.\nVL  - 27\nER  - "
+                .parse()
+                .unwrap();
+
+        let edited = entry
+            .clone()
+            .into_builder()
+            .title("New Title")
+            .author("Weaver, Warren")
+            .build();
+
+        assert_eq!(
+            edited,
+            Entry {
+                title: Some(String::from("New Title")),
+                authors: vec![
+                    String::from("Shannon, Claude E."),
+                    String::from("Weaver, Warren")
+                ],
+                ..entry.clone()
+            }
+        );
+        assert_eq!(EntryBuilder::from_entry(entry.clone()).build(), entry);
+    }
+
+    #[test]
+    fn primary_date_tag() {
+        let ris = RIS(vec![
+            ris_entry! {
+                type: Journal,
+                year: 1948,
+            },
+            Entry {
+                primary_date: Some(PublicationDate::new(1948, Some(7), None, None)),
+                ..Entry::new(ReferenceType::Journal)
+            },
+        ]);
+        let options = SerializeOptions {
+            primary_date_tag: Y1OrPy::Py,
+            ..SerializeOptions::default()
+        };
+
+        assert_eq!(
+            ris.to_string(),
+            "TY  - JOUR\nY1  - 1948///\nER  - \nTY  - JOUR\nY1  - 1948/07//\nER  - "
+        );
+
+        let s = ris.to_string_with_options(&options);
+        assert_eq!(
+            s,
+            "TY  - JOUR\nPY  - 1948\nER  - \nTY  - JOUR\nY1  - 1948/07//\nER  - "
+        );
+        assert_eq!(s.parse::<RIS>().unwrap(), ris);
+    }
+
+    #[test]
+    fn serial_number_validation() {
+        let mut entry = Entry::new(ReferenceType::Journal);
+        assert_eq!(entry.serial_number_is_valid(), None);
+
+        for (serial_number, valid) in &[
+            ("0378-5955", true),
+            ("0378-5954", false),
+            ("2434-561X", true),
+            ("0-306-40615-2", true),
+            ("0-306-40615-3", false),
+            ("0-8044-2957-X", true),
+            ("978-0-306-40615-7", true),
+            ("978 0 306 40615 6", false),
+            ("978-0-306-40615-X", false),
+            ("12345", false),
+            ("ISSN 0378-5955", false),
+        ] {
+            entry.serial_number = Some(serial_number.to_string());
+            assert_eq!(
+                entry.serial_number_is_valid(),
+                Some(*valid),
+                "{}",
+                serial_number
+            );
+        }
+    }
+
+    #[test]
+    fn no_trailing_newline() {
+        for s in &[
+            "TY  - JOUR\nT1  - First\nER  - \nTY  - BOOK\nT1  - Last\nER  - ",
+            "TY  - JOUR\nT1  - First\nER  - \nTY  - BOOK\nT1  - Last\nER  -",
+            "TY  - JOUR\r\nT1  - First\r\nER  - \r\nTY  - BOOK\r\nT1  - Last\r\nER  -",
+        ] {
+            let ris: RIS = s.parse().unwrap();
+            assert_eq!(ris.0.len(), 2);
+            assert_eq!(ris.0[1].title.as_deref(), Some("Last"));
+
+            assert_eq!(RIS::from_reader(s.as_bytes()).unwrap(), ris);
+
+            let entries: Vec<_> = EntryReader::new(io::BufReader::with_capacity(4, s.as_bytes()))
+                .collect::<Result<_, _>>()
+                .unwrap();
+            assert_eq!(entries, ris.0);
+        }
+    }
+
+    #[test]
+    fn filter_and_retain() {
+        let mut ris = RIS(vec![
+            ris_entry! { type: Journal, doi: "10.1000/182" },
+            ris_entry! { type: Journal, title: "No DOI" },
+            ris_entry! { type: WholeBook, doi: "10.1000/183" },
+        ]);
+
+        let with_doi = ris.filter(|entry| entry.doi.is_some());
+        assert_eq!(with_doi.0, vec![ris.0[0].clone(), ris.0[2].clone()]);
+        assert_eq!(ris.0.len(), 3);
+
+        ris.retain(|entry| entry.doi.is_some());
+        assert_eq!(ris, with_doi);
+    }
 
-        Ok(Self::new(year, month, day, other_info))
+    #[test]
+    fn entry_template() {
+        let template = Entry::template(ReferenceType::Journal);
+        assert!(template.is_empty());
+        assert_eq!(template.reference_type, ReferenceType::Journal);
+
+        let required = template.required_fields();
+        assert!(required.contains(&"title"));
+        assert!(required.contains(&"journal"));
+        assert_eq!(template.missing_recommended_fields(), required);
+
+        assert_eq!(
+            Entry::template(ReferenceType::Generic).required_fields(),
+            ["title"]
+        );
     }
-}
 
-impl Display for PublicationDate {
-    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
-        write!(f, "{:04}/", self.year)?;
+    #[test]
+    fn unknown_tags_in_exports() {
+        let ris = RIS::from_str_with_options(
+            "TY  - JOUR\nT1  - A Title\nXX  - first\nDP  - Wiley\nXX  - \"second\"\nER  - ",
+            &ParseOptions {
+                preserve_unknown_tags: true,
+                ..ParseOptions::default()
+            },
+        )
+        .unwrap();
+        let entry = &ris.0[0];
+
+        assert_eq!(
+            entry
+                .iter_tags()
+                .map(|(tag, value)| format!("{}={}", tag, value))
+                .collect::<Vec<_>>(),
+            vec![
+                "TY=JOUR",
+                "T1=A Title",
+                "XX=first",
+                "DP=Wiley",
+                "XX=\"second\""
+            ]
+        );
 
-        if let Some(month) = self.month {
-            write!(f, "{:02}", month)?;
-        }
+        let map = entry.to_map();
+        assert_eq!(map["XX"], vec!["first", "\"second\""]);
+        assert_eq!(map["DP"], vec!["Wiley"]);
+        assert_eq!(map["T1"], vec!["A Title"]);
 
-        write!(f, "/")?;
+        assert_eq!(
+            entry.to_json(),
+            r#"{"reference_type":"JOUR","title":"A Title","unknown_tags":{"XX":["first","\"second\""],"DP":["Wiley"]}}"#
+        );
+        assert!(ris.to_ndjson().contains(r#""unknown_tags":{"XX""#));
+    }
 
-        if let Some(day) = self.day {
-            write!(f, "{:02}", day)?;
-        }
+    #[test]
+    fn citation_graph() {
+        let ris = RIS(vec![
+            ris_entry! {
+                type: Journal,
+                title: "A Mathematical Theory of Communication",
+                year: 1948,
+                doi: "10.1002/j.1538-7305.1948.tb01338.x",
+            },
+            ris_entry! {
+                type: Journal,
+                title: "Computing Machinery and Intelligence",
+                year: 1950,
+                cited_references: [
+                    "Shannon CE, 1948, BELL SYST TECH J, V27, P379, DOI 10.1002/J.1538-7305.1948.TB01338.X",
+                    "Turing, A. M. (1950). Computing machinery and intelligence. Mind.",
+                ],
+            },
+            ris_entry! {
+                type: Journal,
+                title: "Later Work",
+                year: 1960,
+                cited_references: [
+                    "Turing AM. Computing Machinery and Intelligence. Mind 1950;59:433-460.",
+                    "Shannon CE. A mathematical theory of communication. 1949.",
+                    "Unrelated, 2000, Nowhere",
+                ],
+            },
+        ]);
 
-        write!(f, "/")?;
+        let graph = ris.build_citation_graph();
 
-        if let Some(ref other_info) = self.other_info {
-            write!(f, "{}", other_info)?;
-        }
+        assert_eq!(graph.len(), 2);
+        assert_eq!(graph[&1], vec![0]);
+        assert_eq!(graph[&2], vec![1]);
+        assert!(!graph.contains_key(&0));
+    }
 
-        Ok(())
+    #[test]
+    fn serialize_trailing_newline() {
+        let ris = RIS(vec![
+            ris_entry! { type: Journal, title: "First" },
+            ris_entry! { type: Journal, title: "Second" },
+        ]);
+        let options = SerializeOptions {
+            trailing_newline: true,
+            ..SerializeOptions::default()
+        };
+
+        assert!(ris.to_string().ends_with("ER  - "));
+        assert_eq!(
+            ris.to_string_with_options(&options),
+            "TY  - JOUR\nT1  - First\nER  - \nTY  - JOUR\nT1  - Second\nER  - \n"
+        );
+        assert_eq!(
+            ris.to_string_with_options(&SerializeOptions {
+                line_ending: LineEnding::CrLf,
+                ..options.clone()
+            }),
+            "TY  - JOUR\r\nT1  - First\r\nER  - \r\nTY  - JOUR\r\nT1  - Second\r\nER  - \r\n"
+        );
+        assert_eq!(RIS(vec![]).to_string_with_options(&options), "");
+        assert_eq!(
+            RIS::from_str(&ris.to_string_with_options(&options)).unwrap(),
+            ris
+        );
     }
-}
 
-/// An error occurring during the parsing of a RIS file.
-#[derive(Debug, Clone, Copy)]
-pub struct ParseError {
-    /// The line number (starting at 1) on which the error occurred.
-    pub line_no: usize,
-    /// The kind of error
-    pub kind: ParseErrorKind,
-}
+    #[test]
+    fn sanitize() {
+        let mut entry = ris_entry! {
+            type: Other(String::from(" jour")),
+            title: "  A Mathematical Theory\r\nof Communication ",
+            doi: "https://doi.org/10.1002/j.1538-7305.1948.tb01338.x",
+            keywords: ["Information", " information ", "entropy"],
+        };
+        let original = entry.clone();
 
-impl ParseError {
-    pub fn new(line_no: usize, kind: ParseErrorKind) -> Self {
-        Self { line_no, kind }
+        entry.sanitize(SanitizeOptions::default());
+
+        assert_eq!(entry.reference_type, ReferenceType::Journal);
+        assert_eq!(
+            entry.title.as_deref(),
+            Some("A Mathematical Theory\nof Communication")
+        );
+        assert_eq!(
+            entry.doi.as_deref(),
+            Some("10.1002/j.1538-7305.1948.tb01338.x")
+        );
+        assert_eq!(entry.keywords, vec!["Information", "entropy"]);
+
+        let mut entry = original.clone();
+        entry.sanitize(SanitizeOptions {
+            trim_values: false,
+            normalize_doi: false,
+            canonicalize_type: false,
+            ..SanitizeOptions::default()
+        });
+
+        assert_eq!(entry.reference_type, original.reference_type);
+        assert_eq!(
+            entry.title.as_deref(),
+            Some("  A Mathematical Theory\nof Communication ")
+        );
+        assert_eq!(entry.doi, original.doi);
+        assert_eq!(
+            entry.keywords,
+            vec!["Information", " information ", "entropy"]
+        );
     }
-}
 
-impl Display for ParseError {
-    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
-        match self.kind {
-            ParseErrorKind::TagOutsideEntry => write!(f, "Tag outside entry"),
-            ParseErrorKind::UnterminatedEntry => write!(f, "Unterminated entry"),
-            ParseErrorKind::InvalidKey => write!(f, "Invalid key"),
-            ParseErrorKind::InvalidLine => write!(f, "Invalid line format"),
-            ParseErrorKind::DuplicateField => write!(f, "Duplicate field"),
-            ParseErrorKind::InvalidDate => write!(f, "Invalid date format"),
-        }?;
+    #[test]
+    fn skip_empty_repeated() {
+        let s = "TY  - JOUR\nKW  - entropy\nKW  - \nKW  -  \nA1  - \nER  - ";
 
-        write!(f, " at line {}", self.line_no)?;
+        let entry = &RIS::from_str(s).unwrap().0[0];
+        assert_eq!(entry.keywords, vec!["entropy", "", " "]);
+        assert_eq!(entry.authors, vec![""]);
 
-        Ok(())
+        let options = ParseOptions {
+            skip_empty_repeated: true,
+            ..ParseOptions::default()
+        };
+        let entry = &RIS::from_str_with_options(s, &options).unwrap().0[0];
+        assert_eq!(entry.keywords, vec!["entropy"]);
+        assert!(entry.authors.is_empty());
     }
-}
 
-impl std::error::Error for ParseError {}
+    #[test]
+    fn reorder_by() {
+        let mut ris = RIS(vec![
+            ris_entry! { type: Journal, title: "No DOI" },
+            ris_entry! { type: Journal, title: "First", doi: "10.1000/first" },
+            ris_entry! { type: Journal, title: "Uncited", doi: "10.1000/uncited" },
+            ris_entry! { type: Journal, title: "Second", doi: "https://doi.org/10.1000/SECOND" },
+            ris_entry! { type: Journal, title: "Third", doi: "10.1000/third" },
+        ]);
+        let order = [
+            "10.1000/third",
+            "10.1000/second",
+            "10.1000/missing",
+            "10.1000/first",
+        ]
+        .iter()
+        .map(|doi| String::from(*doi))
+        .collect::<Vec<_>>();
+
+        ris.reorder_by(&order, |entry| {
+            entry.normalized_doi().map(str::to_lowercase)
+        });
+
+        let titles: Vec<_> = ris
+            .0
+            .iter()
+            .map(|entry| entry.title.as_deref().unwrap())
+            .collect();
+        assert_eq!(
+            titles,
+            vec!["Third", "Second", "First", "No DOI", "Uncited"]
+        );
+    }
 
-/// The kind of an error occurring during the parsing of a RIS file.
-#[derive(Debug, Clone, Copy)]
-pub enum ParseErrorKind {
-    /// A tag other than `TY` was present outside of an entry.
-    TagOutsideEntry,
-    /// An entry was not terminated by an `ER` tag.
-    UnterminatedEntry,
-    /// An invalid key was encountered.
-    InvalidKey,
-    /// A line was not in the RIS format of `<letter><letter_or_number><space><space><dash><space><any>*`.
-    InvalidLine,
-    /// A unique field was present multiple times in a single entry.
-    DuplicateField,
-    /// A date field was not in the `YYYY/MM/DD/otherinfo` format.
-    InvalidDate,
-}
+    #[test]
+    fn address_and_accession_number() {
+        let s = "TY  - JOUR\nAN  - PMID: 12345678\nAD  - Bell Labs, Murray Hill, NJ\nER  - ";
 
-#[cfg(test)]
-mod test {
-    use super::*;
+        let entry = &RIS::from_str(s).unwrap().0[0];
+        assert_eq!(entry.address.as_deref(), Some("Bell Labs, Murray Hill, NJ"));
+        assert_eq!(entry.accession_number.as_deref(), Some("PMID: 12345678"));
 
-    use pretty_assertions::assert_eq;
+        let borrowed = &parse_borrowed(s).unwrap()[0];
+        assert_eq!(borrowed.address, Some("Bell Labs, Murray Hill, NJ"));
+        assert_eq!(borrowed.accession_number, Some("PMID: 12345678"));
+
+        assert_eq!(entry.to_string(), s);
+    }
 
     #[test]
-    fn deserialize_one_record() {
-        let s = "TY  - JOUR
-AU  - Shannon, Claude E.
-PY  - 1948/07//
-TI  - A Mathematical Theory of Communication
-T2  - Bell System Technical Journal
-SP  - 379
-EP  - 423
-VL  - 27
-ER  - ";
+    fn transform_stream_strips_tag() {
+        let input = "\u{FEFF}TY  - JOUR\r\nUR  - https://example.com/a\r\nTI  - Title\r\nUR  - https://example.com/b\r\nER  - \r\n\r\nTY  - BOOK\nUR  - https://example.com/c\nkw  - not a tag\nER  -";
+        let mut output = Vec::new();
+
+        transform_stream(input.as_bytes(), &mut output, |tag, value| match tag {
+            "UR" => None,
+            "TI" => Some((String::from("T1"), value.to_uppercase())),
+            _ => Some((String::from(tag), String::from(value))),
+        })
+        .unwrap();
 
-        let ris = RIS(vec![Entry {
-            authors: vec![String::from("Shannon, Claude E.")],
-            primary_date: Some(PublicationDate::new(1948, Some(7), None, None)),
-            title: Some(String::from("A Mathematical Theory of Communication")),
-            secondary_title: Some(String::from("Bell System Technical Journal")),
-            start_page: Some(String::from("379")),
-            end_page: Some(String::from("423")),
-            volume: Some(String::from("27")),
-            ..Entry::new(ReferenceType::Journal)
-        }]);
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "TY  - JOUR\r\nT1  - TITLE\r\nER  - \r\n\r\nTY  - BOOK\nkw  - not a tag\nER  - "
+        );
+    }
 
-        assert_eq!(ris, RIS::from_str(s).unwrap());
+    #[test]
+    fn m3_doi_as_doi() {
+        let options = ParseOptions {
+            m3_doi_as_doi: true,
+            ..ParseOptions::default()
+        };
+        let doi = "TY  - JOUR\nM3  - https://doi.org/10.1002/j.1538-7305.1948.tb01338.x\nER  - ";
+        let text = "TY  - JOUR\nM3  - Journal Article\nER  - ";
+        let existing = "TY  - JOUR\nDO  - 10.1000/existing\nM3  - 10.1000/other\nER  - ";
+
+        let entry = &RIS::from_str_with_options(doi, &options).unwrap().0[0];
+        assert_eq!(
+            entry.doi.as_deref(),
+            Some("https://doi.org/10.1002/j.1538-7305.1948.tb01338.x")
+        );
+        assert_eq!(entry.misc_3, None);
+
+        let entry = &RIS::from_str_with_options(text, &options).unwrap().0[0];
+        assert_eq!(entry.doi, None);
+        assert_eq!(entry.misc_3.as_deref(), Some("Journal Article"));
+
+        let entry = &RIS::from_str_with_options(existing, &options).unwrap().0[0];
+        assert_eq!(entry.doi.as_deref(), Some("10.1000/existing"));
+        assert_eq!(entry.misc_3.as_deref(), Some("10.1000/other"));
+
+        let entry = &RIS::from_str(doi).unwrap().0[0];
+        assert_eq!(entry.doi, None);
+        assert!(entry.misc_3.is_some());
     }
 
     #[test]
-    fn deserialize_two_records() {
-        let s = "TY  - JOUR
-AU  - Shannon, Claude E.
-PY  - 1948/07//
-TI  - A Mathematical Theory of Communication
-T2  - Bell System Technical Journal
-SP  - 379
-EP  - 423
-VL  - 27
-ER  - \nTY  - JOUR
-T1  - On computable numbers, with an application to the Entscheidungsproblem
-A1  - Turing, Alan Mathison
-JO  - Proc. of London Mathematical Society
-VL  - 47
-IS  - 1
-SP  - 230
-EP  - 265
-Y1  - 1937
-ER  - ";
+    fn dedup_ignores_empty_dois() {
+        let mut ris = RIS::from_str(
+            "TY  - JOUR\nT1  - A\nDO  - \nER  - \nTY  - JOUR\nT1  - B\nDO  - \nER  - \nTY  - JOUR\nT1  - A\nDO  -  \nER  - ",
+        )
+        .unwrap();
+
+        ris.dedup_keep_most_complete();
+
+        let titles: Vec<_> = ris
+            .0
+            .iter()
+            .map(|entry| entry.title.as_deref().unwrap())
+            .collect();
+        assert_eq!(titles, vec!["A", "B"]);
+    }
 
-        let ris = RIS(vec![
-            Entry {
-                authors: vec![String::from("Shannon, Claude E.")],
-                primary_date: Some(PublicationDate::new(1948, Some(7), None, None)),
-                title: Some(String::from("A Mathematical Theory of Communication")),
-                secondary_title: Some(String::from("Bell System Technical Journal")),
-                start_page: Some(String::from("379")),
-                end_page: Some(String::from("423")),
-                volume: Some(String::from("27")),
-                ..Entry::new(ReferenceType::Journal)
-            },
-            Entry {
-                title: Some(String::from(
-                    "On computable numbers, with an application to the Entscheidungsproblem",
-                )),
-                authors: vec![String::from("Turing, Alan Mathison")],
-                journal: Some(String::from("Proc. of London Mathematical Society")),
-                volume: Some(String::from("47")),
-                issue: Some(String::from("1")),
-                start_page: Some(String::from("230")),
-                end_page: Some(String::from("265")),
-                primary_date: Some(PublicationDate::new(1937, None, None, None)),
-                ..Entry::new(ReferenceType::Journal)
+    #[test]
+    fn m3_doi_before_do() {
+        let options = ParseOptions {
+            m3_doi_as_doi: true,
+            ..ParseOptions::default()
+        };
+
+        let entry = &RIS::from_str_with_options(
+            "TY  - JOUR\nM3  - 10.1000/x\nDO  - 10.1000/x\nER  - ",
+            &options,
+        )
+        .unwrap()
+        .0[0];
+        assert_eq!(entry.doi.as_deref(), Some("10.1000/x"));
+        assert_eq!(entry.misc_3.as_deref(), Some("10.1000/x"));
+
+        let entry =
+            &RIS::from_str_with_options("TY  - JOUR\nM3  - 10.1000/x\nDO  - \nER  - ", &options)
+                .unwrap()
+                .0[0];
+        assert_eq!(entry.doi.as_deref(), Some("10.1000/x"));
+        assert_eq!(entry.misc_3, None);
+
+        let entry = &RIS::from_str_with_options(
+            "TY  - JOUR\nM3  - 10.1000/x",
+            &ParseOptions {
+                finalize_at_eof: true,
+                ..options
             },
-        ]);
+        )
+        .unwrap()
+        .0[0];
+        assert_eq!(entry.doi.as_deref(), Some("10.1000/x"));
+    }
 
-        assert_eq!(ris, RIS::from_str(s).unwrap());
+    #[test]
+    fn multiline_blank_lines() {
+        let options = ParseOptions {
+            multiline_values: true,
+            ..ParseOptions::default()
+        };
+
+        let entry = &RIS::from_str_with_options("TY  - JOUR\n\nT1  - t\n\nER  - ", &options)
+            .unwrap()
+            .0[0];
+        assert_eq!(entry.reference_type, ReferenceType::Journal);
+        assert_eq!(entry.title.as_deref(), Some("t"));
+
+        let entry = &RIS::from_str_with_options(
+            "TY  - JOUR\nAB  - First\n\n  \nsecond\n \nN1  - Note\n\n\nER  - ",
+            &options,
+        )
+        .unwrap()
+        .0[0];
+        assert_eq!(entry.abstract_.as_deref(), Some("First\n\n\nsecond"));
+        assert_eq!(entry.notes.as_deref(), Some("Note"));
     }
 
     #[test]
-    fn serialize_one_record() {
-        let ris = RIS(vec![Entry {
-            authors: vec![String::from("Shannon, Claude E.")],
-            primary_date: Some(PublicationDate::new(1948, Some(7), None, None)),
-            title: Some(String::from("A Mathematical Theory of Communication")),
-            secondary_title: Some(String::from("Bell System Technical Journal")),
-            start_page: Some(String::from("379")),
-            end_page: Some(String::from("423")),
-            volume: Some(String::from("27")),
-            ..Entry::new(ReferenceType::Journal)
-        }]);
+    fn multiline_never_joins_type() {
+        let options = ParseOptions {
+            multiline_values: true,
+            ..ParseOptions::default()
+        };
 
-        let s = "TY  - JOUR
-T1  - A Mathematical Theory of Communication
-T2  - Bell System Technical Journal
-A1  - Shannon, Claude E.
-Y1  - 1948/07//
-SP  - 379
-EP  - 423
-VL  - 27
-ER  - ";
+        let err = RIS::from_str_with_options("TY  - JOUR\narticle\nER  - ", &options).unwrap_err();
+        assert_eq!(err.line_no, 2);
+        assert!(matches!(err.kind, ParseErrorKind::InvalidLine));
+    }
 
-        assert_eq!(ris.to_string(), s);
+    #[test]
+    fn n2_as_notes_duplicate_n1() {
+        let options = ParseOptions {
+            n2_as_notes: true,
+            ..ParseOptions::default()
+        };
+
+        let err = RIS::from_str_with_options("TY  - JOUR\nN1  - a\nN1  - b\nER  - ", &options)
+            .unwrap_err();
+        assert_eq!(err.line_no, 3);
+        assert!(matches!(err.kind, ParseErrorKind::DuplicateField));
+
+        let ris =
+            RIS::from_str_with_options("TY  - JOUR\nN2  - b\nN1  - a\nN2  - c\nER  - ", &options)
+                .unwrap();
+        assert_eq!(ris.0[0].notes.as_deref(), Some("a\nb\nc"));
+        assert!(ris.0[0].unknown_tags.is_empty());
+
+        let mut aliases = HashMap::new();
+        aliases.insert(String::from("XN"), String::from("N2"));
+        let options = ParseOptions {
+            n2_as_notes: true,
+            preserve_unknown_tags: true,
+            ..ParseOptions::with_tag_aliases(aliases)
+        };
+        let ris =
+            RIS::from_str_with_options("TY  - JOUR\nXN  - b\nN1  - a\nER  - ", &options).unwrap();
+        assert_eq!(ris.0[0].notes.as_deref(), Some("a\nb"));
+        assert!(ris.0[0].unknown_tags.is_empty());
     }
 
     #[test]
-    fn serialize_two_records() {
-        let ris = RIS(vec![
-            Entry {
-                authors: vec![String::from("Shannon, Claude E.")],
-                primary_date: Some(PublicationDate::new(1948, Some(7), None, None)),
-                title: Some(String::from("A Mathematical Theory of Communication")),
-                secondary_title: Some(String::from("Bell System Technical Journal")),
-                start_page: Some(String::from("379")),
-                end_page: Some(String::from("423")),
-                volume: Some(String::from("27")),
-                ..Entry::new(ReferenceType::Journal)
-            },
-            Entry {
-                title: Some(String::from(
-                    "On computable numbers, with an application to the Entscheidungsproblem",
-                )),
-                authors: vec![String::from("Turing, Alan Mathison")],
-                journal: Some(String::from("Proc. of London Mathematical Society")),
-                volume: Some(String::from("47")),
-                issue: Some(String::from("1")),
-                start_page: Some(String::from("230")),
-                end_page: Some(String::from("265")),
-                primary_date: Some(PublicationDate::new(1937, None, None, None)),
-                ..Entry::new(ReferenceType::Journal)
-            },
-        ]);
+    fn skip_empty_entries_at_eof() {
+        let options = ParseOptions {
+            finalize_at_eof: true,
+            skip_empty_entries: true,
+            ..ParseOptions::default()
+        };
 
-        let s = "TY  - JOUR
-T1  - A Mathematical Theory of Communication
-T2  - Bell System Technical Journal
-A1  - Shannon, Claude E.
-Y1  - 1948/07//
-SP  - 379
-EP  - 423
-VL  - 27
-ER  - \nTY  - JOUR
-T1  - On computable numbers, with an application to the Entscheidungsproblem
-A1  - Turing, Alan Mathison
-Y1  - 1937///
-SP  - 230
-EP  - 265
-JF  - Proc. of London Mathematical Society
-VL  - 47
-IS  - 1
-ER  - ";
+        let ris =
+            RIS::from_str_with_options("TY  - JOUR\nT1  - Title\nER  - \nTY  - GEN", &options)
+                .unwrap();
+        assert_eq!(ris.0.len(), 1);
+        assert_eq!(ris.0[0].title.as_deref(), Some("Title"));
 
-        assert_eq!(ris.to_string(), s);
+        let ris = RIS::from_str_with_options("TY  - GEN\nT1  - Truncated", &options).unwrap();
+        assert_eq!(ris.0.len(), 1);
+
+        let entries: Vec<_> = EntryReader::with_options("TY  - GEN\n".as_bytes(), options)
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert!(entries.is_empty());
     }
 
+    #[test]
+    fn borrowed_and_owned_parsers_share_tags() {
+        for tag in KNOWN_TAGS.iter().filter(|tag| !["TY", "ER"].contains(tag)) {
+            let value = if ["Y1", "PY", "DA", "Y2"].contains(tag) {
+                "2000"
+            } else {
+                "value"
+            };
+            let s = format!("TY  - BOOK\n{}  - {}\nER  - ", tag, value);
+
+            assert!(
+                field_for(tag, &ReferenceType::WholeBook).is_some(),
+                "{}",
+                tag
+            );
+            assert_eq!(
+                parse_borrowed(&s).unwrap()[0].to_owned(),
+                RIS::from_str(&s).unwrap().0[0],
+                "{}",
+                tag
+            );
+        }
+    }
 }